@@ -0,0 +1,123 @@
+// Compares the cost of a cache hit when probing with an owned `(u8, String)`
+// key hashed with SipHash (the std default, as the cache used to) against a
+// borrowed `(u8, &str)` view hashed with foldhash, as `StemCache` does now.
+// The two mixed variants show how much each change contributes. The last
+// rows time `StemCache` itself, and then on a few hot words repeated, which
+// is what its thread-local L1 cache is for. Run with
+// `cargo bench --bench cache_lookup`.
+
+use py_rust_stemmers::cache::{CacheKey, KeyView, StemCache, DEFAULT_L1_CAPACITY};
+use py_rust_stemmers::Admission;
+use std::collections::hash_map::RandomState as SipHash;
+use std::collections::HashMap;
+use std::hash::BuildHasher;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const ROUNDS: usize = 200;
+const WORDS: usize = 10_000;
+const HOT_WORDS: usize = 64;
+
+// Word-like keys of typical lengths, all present in the map
+fn words() -> Vec<String> {
+    const SUFFIXES: [&str; 8] = ["", "s", "ing", "ed", "ness", "ation", "ly", "fulness"];
+    (0..WORDS)
+        .map(|i| format!("w{:x}{}", i * 2_654_435_761 % 1_000_003, SUFFIXES[i % 8]))
+        .collect()
+}
+
+fn stem(word: &str) -> String {
+    word[..word.len() / 2].to_owned()
+}
+
+fn filled<S: BuildHasher>(words: &[String], hasher: S) -> HashMap<CacheKey, String, S> {
+    let mut map = HashMap::with_capacity_and_hasher(words.len(), hasher);
+    for word in words {
+        map.insert((0, word.clone()), stem(word));
+    }
+    map
+}
+
+// A single-shard cache holding every word; an `l1_capacity` of 0 disables
+// the L1 cache
+fn filled_cache(words: &[String], l1_capacity: usize) -> StemCache {
+    let cache = StemCache::new(None, None, 1, l1_capacity);
+    for word in words {
+        cache.insert(0, word, stem(word), Admission::Always);
+    }
+    cache
+}
+
+fn time(mut f: impl FnMut()) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        f();
+    }
+    start.elapsed()
+}
+
+fn owned<S: BuildHasher>(map: &HashMap<CacheKey, String, S>, words: &[String]) -> Duration {
+    time(|| {
+        for word in words {
+            black_box(map.get(&(0u8, black_box(word).clone())));
+        }
+    })
+}
+
+fn borrowed<S: BuildHasher>(map: &HashMap<CacheKey, String, S>, words: &[String]) -> Duration {
+    time(|| {
+        for word in words {
+            let key = (0u8, black_box(word.as_str()));
+            black_box(map.get(&key as &dyn KeyView));
+        }
+    })
+}
+
+fn cached(cache: &StemCache, words: &[String]) -> Duration {
+    // Fill the L1 cache, if any, before timing
+    cached_round(cache, words);
+    time(|| cached_round(cache, words))
+}
+
+fn cached_round(cache: &StemCache, words: &[String]) {
+    let mut session = cache.session();
+    for word in words {
+        black_box(session.get(0, black_box(word), Admission::Always));
+    }
+}
+
+fn main() {
+    let words = words();
+    let hot: Vec<String> = words[..HOT_WORDS]
+        .iter()
+        .cycle()
+        .take(WORDS)
+        .cloned()
+        .collect();
+    let cache = filled_cache(&words, 0);
+    let l1_cache = filled_cache(&words, DEFAULT_L1_CAPACITY);
+    let sip = filled(&words, SipHash::new());
+    let fold = filled(&words, foldhash::fast::RandomState::default());
+
+    let baseline = owned(&sip, &words);
+    let variants = [
+        ("owned key + SipHash", baseline),
+        ("owned key + foldhash", owned(&fold, &words)),
+        ("borrowed key + SipHash", borrowed(&sip, &words)),
+        ("borrowed key + foldhash", borrowed(&fold, &words)),
+        ("StemCache", cached(&cache, &words)),
+        ("StemCache, hot words", cached(&cache, &hot)),
+        ("StemCache + L1, hot words", cached(&l1_cache, &hot)),
+    ];
+
+    println!("{:<26} {:>12} {:>9}", "lookup", "ns/hit", "speedup");
+    let lookups = (ROUNDS * words.len()) as f64;
+    for (name, elapsed) in variants {
+        println!(
+            "{:<26} {:>12.1} {:>8.2}x",
+            name,
+            elapsed.as_nanos() as f64 / lookups,
+            baseline.as_secs_f64() / elapsed.as_secs_f64()
+        );
+    }
+}
@@ -0,0 +1,237 @@
+// Compares building a `Stemmer` per word against reusing one instance, for
+// every algorithm. Run with `cargo bench --bench stemmer_reuse`.
+
+use rust_stemmers::{Algorithm, Stemmer};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const ROUNDS: usize = 20_000;
+
+const SAMPLES: [(&str, Algorithm, &[&str]); 18] = [
+    (
+        "arabic",
+        Algorithm::Arabic,
+        &["المكتبات", "والكتاب", "يكتبون", "المدرسة", "بالمعلمين"],
+    ),
+    (
+        "danish",
+        Algorithm::Danish,
+        &[
+            "undersøgelserne",
+            "hurtigere",
+            "bøgerne",
+            "arbejdede",
+            "venligst",
+        ],
+    ),
+    (
+        "dutch",
+        Algorithm::Dutch,
+        &[
+            "ontwikkelingen",
+            "gelukkiger",
+            "boeken",
+            "werkten",
+            "huizen",
+        ],
+    ),
+    (
+        "english",
+        Algorithm::English,
+        &[
+            "running",
+            "computations",
+            "happiness",
+            "fruitlessly",
+            "generously",
+        ],
+    ),
+    (
+        "finnish",
+        Algorithm::Finnish,
+        &[
+            "kirjastoissa",
+            "taloissamme",
+            "juoksemassa",
+            "ystävällisesti",
+            "kaupungeissa",
+        ],
+    ),
+    (
+        "french",
+        Algorithm::French,
+        &[
+            "continuellement",
+            "habitations",
+            "mangeaient",
+            "heureusement",
+            "nationalité",
+        ],
+    ),
+    (
+        "german",
+        Algorithm::German,
+        &[
+            "aufeinanderfolgenden",
+            "häuser",
+            "schönheit",
+            "arbeitete",
+            "freundlichkeit",
+        ],
+    ),
+    (
+        "greek",
+        Algorithm::Greek,
+        &[
+            "καλημέρες",
+            "βιβλιοθήκες",
+            "τρέχοντας",
+            "ανθρώπων",
+            "εργαζόμενοι",
+        ],
+    ),
+    (
+        "hungarian",
+        Algorithm::Hungarian,
+        &[
+            "házakban",
+            "barátaimmal",
+            "könyveket",
+            "gyorsabban",
+            "városokban",
+        ],
+    ),
+    (
+        "italian",
+        Algorithm::Italian,
+        &[
+            "abbandonarono",
+            "felicemente",
+            "nazionalità",
+            "mangiavano",
+            "libreria",
+        ],
+    ),
+    (
+        "norwegian",
+        Algorithm::Norwegian,
+        &[
+            "undersøkelsene",
+            "raskere",
+            "bøkene",
+            "arbeidet",
+            "vennligst",
+        ],
+    ),
+    (
+        "portuguese",
+        Algorithm::Portuguese,
+        &[
+            "computadores",
+            "felicidade",
+            "correndo",
+            "nacionalidade",
+            "livrarias",
+        ],
+    ),
+    (
+        "romanian",
+        Algorithm::Romanian,
+        &[
+            "calculatoarele",
+            "fericirea",
+            "alergând",
+            "naționalitate",
+            "bibliotecile",
+        ],
+    ),
+    (
+        "russian",
+        Algorithm::Russian,
+        &[
+            "библиотеках",
+            "счастливыми",
+            "бегущий",
+            "национальность",
+            "компьютерами",
+        ],
+    ),
+    (
+        "spanish",
+        Algorithm::Spanish,
+        &[
+            "computaciones",
+            "felicidad",
+            "corriendo",
+            "nacionalidad",
+            "frutalmente",
+        ],
+    ),
+    (
+        "swedish",
+        Algorithm::Swedish,
+        &[
+            "undersökningarna",
+            "snabbare",
+            "böckerna",
+            "arbetade",
+            "vänligen",
+        ],
+    ),
+    (
+        "tamil",
+        Algorithm::Tamil,
+        &["புத்தகங்கள்", "வீடுகளில்", "ஓடுகிறான்", "மாணவர்களுக்கு", "நகரங்களில்"],
+    ),
+    (
+        "turkish",
+        Algorithm::Turkish,
+        &[
+            "kitaplarımızdan",
+            "evlerde",
+            "koşuyorum",
+            "arkadaşlarımla",
+            "şehirlerde",
+        ],
+    ),
+];
+
+fn time(mut f: impl FnMut()) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        f();
+    }
+    start.elapsed()
+}
+
+fn main() {
+    println!(
+        "{:<12} {:>16} {:>16} {:>9}",
+        "algorithm", "create/word ns", "reused ns", "speedup"
+    );
+    for (name, algorithm, words) in SAMPLES {
+        let per_word = time(|| {
+            for word in words {
+                black_box(Stemmer::create(black_box(algorithm)).stem(black_box(word)));
+            }
+        });
+
+        let stemmer = Stemmer::create(algorithm);
+        let reused = time(|| {
+            for word in words {
+                black_box(stemmer.stem(black_box(word)));
+            }
+        });
+
+        let calls = (ROUNDS * words.len()) as f64;
+        let per_word_ns = per_word.as_nanos() as f64 / calls;
+        let reused_ns = reused.as_nanos() as f64 / calls;
+        println!(
+            "{:<12} {:>16.1} {:>16.1} {:>8.2}x",
+            name,
+            per_word_ns,
+            reused_ns,
+            per_word_ns / reused_ns
+        );
+    }
+}
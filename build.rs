@@ -0,0 +1,31 @@
+// Exposes the locked rust-stemmers version as `RUST_STEMMERS_VERSION`, so
+// cache snapshots record the version that actually produced their stems.
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+fn locked_version(lock: &str, package: &str) -> Option<String> {
+    let name = format!("name = \"{}\"", package);
+    let mut lines = lock.lines().map(str::trim);
+    lines.find(|line| *line == name)?;
+    let version = lines.next()?.strip_prefix("version = \"")?;
+    Some(version.strip_suffix('"')?.to_owned())
+}
+
+fn main() {
+    // Cargo.lock sits next to the manifest of the workspace root, which may
+    // be a parent directory
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let lock_path = manifest_dir
+        .ancestors()
+        .map(|dir| dir.join("Cargo.lock"))
+        .find(|path| path.is_file())
+        .expect("Cargo.lock not found");
+    println!("cargo:rerun-if-changed={}", lock_path.display());
+
+    let lock = fs::read_to_string(&lock_path).expect("Cargo.lock is readable");
+    let version =
+        locked_version(&lock, "rust-stemmers").expect("rust-stemmers is locked in Cargo.lock");
+    println!("cargo:rustc-env=RUST_STEMMERS_VERSION={}", version);
+}
@@ -1,6 +1,487 @@
 """Type stubs for py_rust_stemmers_tuned - High-performance Snowball stemmer implementation."""
 
-from typing import List
+import os
+from typing import Any, Dict, Iterable, List, Mapping, Optional, Set, Tuple, Union
+
+class CacheStats:
+    """
+    Snapshot of the stem cache counters.
+    
+    Attributes:
+        hits: Number of lookups answered from the cache, including l1_hits
+        l1_hits: Number of lookups answered by a thread-local L1 cache
+        misses: Number of lookups that had to run the stemmer
+        inserts: Number of stems written to the cache
+        evictions: Number of entries dropped to stay within the cache limits
+        rejections: Number of stems the admission policy decided not to cache
+        entries: Number of entries currently cached
+        entries_per_language: Current entries keyed by the internal algorithm id
+        memory_bytes: Approximate memory used by the cached entries
+        hit_rate: hits / (hits + misses), or 0.0 before any lookup
+    """
+    
+    hits: int
+    l1_hits: int
+    misses: int
+    inserts: int
+    evictions: int
+    rejections: int
+    entries: int
+    entries_per_language: Dict[int, int]
+    memory_bytes: int
+    
+    @property
+    def hit_rate(self) -> float: ...
+
+class LanguageInfo:
+    """
+    Metadata of a supported language, as returned by supported_languages.
+    
+    Attributes:
+        name: Canonical name accepted by SnowballStemmer, e.g. "english"
+        iso639_1: Two-letter ISO 639-1 code
+        iso639_3: Three-letter ISO 639-3 code
+        aliases: Other names and codes that resolve to this language
+        script: ISO 15924 name of the script the algorithm expects, e.g. "Latin"
+        has_stopwords: Whether a built-in stopword list is available
+        has_exceptions: Whether the algorithm has a built-in list of irregular forms
+    """
+    
+    name: str
+    iso639_1: str
+    iso639_3: str
+    aliases: List[str]
+    script: str
+    has_stopwords: bool
+    has_exceptions: bool
+
+class StemCache:
+    """
+    A stem cache that can be shared between stemmers.
+    
+    Stemmers given the same StemCache (or the same namespace name) share
+    entries, limits and statistics. Different StemCache objects are fully
+    independent, which isolates tenants or pipelines from each other.
+    
+    Entries live in independently locked shards. Each thread also keeps a
+    small L1 cache in front of the shards, so hot words don't contend on a
+    single shard lock. Every 32nd L1 hit on a word is passed on to its shard,
+    which keeps hot words recently used there.
+    
+    Args:
+        max_entries: Maximum number of cached words, or None for no limit (default: 100000)
+        max_bytes: Approximate maximum memory used by cached entries, or None for no limit
+        shards: Number of shards (default: the PY_RUST_STEMMERS_SHARDS environment
+            variable, or scaled to the number of CPUs, at least 32)
+        l1_capacity: Entries in each thread's L1 cache, 0 disables it (default: 256)
+    """
+    
+    def __init__(
+        self,
+        max_entries: Optional[int] = 100_000,
+        max_bytes: Optional[int] = None,
+        shards: Optional[int] = None,
+        l1_capacity: int = 256,
+    ) -> None: ...
+    
+    @staticmethod
+    def named(name: str) -> "StemCache":
+        """
+        Return the cache registered under a namespace name, creating it with
+        default limits on first use. The name "default" is the process-wide cache.
+        """
+        ...
+    
+    @staticmethod
+    def default() -> "StemCache":
+        """Return the process-wide cache used by cache=True."""
+        ...
+    
+    @property
+    def name(self) -> Optional[str]:
+        """The namespace name, or None for caches created directly."""
+        ...
+    
+    def set_limits(
+        self, max_entries: Optional[int] = 100_000, max_bytes: Optional[int] = None
+    ) -> None:
+        """Change the limits of this cache. See set_cache_limits."""
+        ...
+    
+    def get_limits(self) -> Tuple[Optional[int], Optional[int]]:
+        """Return the (max_entries, max_bytes) limits of this cache."""
+        ...
+    
+    @property
+    def shards(self) -> int:
+        """The number of shards of this cache."""
+        ...
+    
+    def set_l1_capacity(self, capacity: int) -> None:
+        """Change the number of entries in each thread's L1 cache, 0 disables it."""
+        ...
+    
+    def get_l1_capacity(self) -> int:
+        """Return the number of entries in each thread's L1 cache."""
+        ...
+    
+    def stats(self) -> CacheStats:
+        """Return the counters of this cache."""
+        ...
+    
+    def clear(self, lang: Optional[str] = None) -> None:
+        """Remove entries from this cache. See clear_cache."""
+        ...
+    
+    def shrink(self) -> None:
+        """Release spare memory held by this cache. See shrink_cache."""
+        ...
+    
+    def save(self, path: Union[str, os.PathLike]) -> int:
+        """Write this cache to a file. See save_cache."""
+        ...
+    
+    def load(self, path: Union[str, os.PathLike]) -> int:
+        """Fill this cache from a file. See load_cache."""
+        ...
+    
+    def __reduce__(self) -> Tuple[Any, Tuple[Any, ...]]:
+        """
+        Pickle support. Namespaces pickle by name and attach to the namespace of
+        the same name when unpickled; other caches unpickle empty, with the same
+        limits, shard count and L1 capacity.
+        """
+        ...
+
+class Tokenizer:
+    """
+    Splits text into words for SnowballStemmer.stem_text() and stem_texts().
+    
+    Words are found with Unicode (UAX #29) word boundaries. Punctuation and
+    whitespace between them are dropped, and tokens without letters (numbers)
+    are never stemmed.
+    
+    Args:
+        lowercase: Lowercase words before stemming, as the stemmers expect (default: True)
+        min_length: Drop tokens shorter than this many characters (default: 1)
+        max_length: Drop tokens longer than this many characters (default: no limit)
+        numbers: Keep tokens without letters, such as "2024" or "3.14" (default: True)
+    
+    Raises:
+        ValueError: If max_length is smaller than min_length
+    """
+    
+    def __init__(
+        self,
+        lowercase: bool = True,
+        min_length: int = 1,
+        max_length: Optional[int] = None,
+        numbers: bool = True,
+    ) -> None: ...
+    
+    @property
+    def lowercase(self) -> bool: ...
+    
+    @property
+    def min_length(self) -> int: ...
+    
+    @property
+    def max_length(self) -> Optional[int]: ...
+    
+    @property
+    def numbers(self) -> bool: ...
+    
+    def tokenize(self, text: str) -> List[str]:
+        """Split text into the tokens stem_text() would stem, without stemming them."""
+        ...
+
+class StemmedToken:
+    """
+    A token returned by SnowballStemmer.stem_text_with_offsets(), with where it
+    appears in the original text. Offsets are [start, end) ranges.
+    """
+    
+    @property
+    def stem(self) -> str: ...
+    
+    @property
+    def surface(self) -> str:
+        """The token as written in the text, before lowercasing."""
+        ...
+    
+    @property
+    def start(self) -> int:
+        """Character offset, so text[start:end] == surface."""
+        ...
+    
+    @property
+    def end(self) -> int: ...
+    
+    @property
+    def byte_start(self) -> int:
+        """Offset into the UTF-8 encoded text."""
+        ...
+    
+    @property
+    def byte_end(self) -> int: ...
+    
+    @property
+    def utf16_start(self) -> int:
+        """Offset in UTF-16 code units, as JavaScript string indices count."""
+        ...
+    
+    @property
+    def utf16_end(self) -> int: ...
+
+class TokenPolicy:
+    """
+    What SnowballStemmer does with tokens that are not words, instead of
+    stemming them. Each class of token takes one of:
+    
+    - "keep": return the token as it is
+    - "drop": leave the token out of the results
+    - "normalize": return a canonical form of the token (see below)
+    
+    Classes left as None are stemmed like words, as without a policy. Tokens
+    returned as they are or normalized are never cached.
+    
+    Args:
+        numbers: Tokens starting with a digit, after an optional sign or currency
+            symbol: "2024", "-3.5", "$20", "3.5GHz". Normalized by lowercasing
+            units and dropping thousands separators ("1,000" -> "1000").
+        urls: Tokens starting with http://, https://, ftp:// or www. Normalized
+            by lowercasing the scheme and host and dropping a trailing "/".
+        emails: Addresses such as "Bob@example.com". Normalized by lowercasing.
+        mentions: "@name" tokens. Normalized by lowercasing.
+        hashtags: "#tag" tokens. Normalized to the stem of the lowercased tag without "#".
+        emoji: Emoji and emoji sequences. Normalized by dropping skin tones and
+            presentation selectors.
+    
+    Raises:
+        ValueError: If a policy is not "keep", "drop" or "normalize"
+    """
+    
+    def __init__(
+        self,
+        numbers: Optional[str] = None,
+        urls: Optional[str] = None,
+        emails: Optional[str] = None,
+        mentions: Optional[str] = None,
+        hashtags: Optional[str] = None,
+        emoji: Optional[str] = None,
+    ) -> None: ...
+    
+    @property
+    def numbers(self) -> Optional[str]: ...
+    
+    @property
+    def urls(self) -> Optional[str]: ...
+    
+    @property
+    def emails(self) -> Optional[str]: ...
+    
+    @property
+    def mentions(self) -> Optional[str]: ...
+    
+    @property
+    def hashtags(self) -> Optional[str]: ...
+    
+    @property
+    def emoji(self) -> Optional[str]: ...
+
+class StemRecorder:
+    """
+    Records which surface forms produced each stem, for query suggestions and
+    facet labels. Pass one to SnowballStemmer(recorder=...); stemmers of the
+    same language can share one. Recording is thread-safe, including from
+    stem_words_parallel(). Words are recorded as given, and stem_text()
+    records each word as it appears in the text, before lowercasing.
+    
+    A pickled recorder carries its counts, but what a copy records (in a
+    worker process, say) is not added to the original.
+    
+    Args:
+        counts: Counts to start from, as returned by export() (default: None)
+    """
+    
+    def __init__(self, counts: Optional[Mapping[str, Mapping[str, int]]] = None) -> None: ...
+    
+    def forms(self, stem: str) -> List[Tuple[str, int]]:
+        """The surface forms of a stem with their counts, most frequent first."""
+        ...
+    
+    def display_form(self, stem: str) -> Optional[str]:
+        """
+        The most frequent surface form of a stem (alphabetically first on a
+        tie), or None if the stem was never recorded.
+        """
+        ...
+    
+    def display_forms(self) -> Dict[str, str]:
+        """display_form() of every recorded stem."""
+        ...
+    
+    def export(self) -> Dict[str, Dict[str, int]]:
+        """Everything recorded, as {stem: {surface form: count}}."""
+        ...
+    
+    def clear(self) -> None: ...
+    
+    def __len__(self) -> int:
+        """The number of distinct stems recorded."""
+        ...
+
+class CacheWarmup:
+    """Handle to a cache warm-up running on a background thread."""
+    
+    @property
+    def done(self) -> bool:
+        """Whether the warm-up has finished."""
+        ...
+    
+    def wait(self) -> int:
+        """
+        Block until the warm-up finishes.
+        
+        Returns:
+            The number of words stemmed into the cache
+        
+        Raises:
+            OSError: If the vocabulary file could not be read
+        """
+        ...
+
+class LanguageDetection:
+    """
+    Result of language detection.
+    
+    Attributes:
+        language: Canonical name of the language to stem with
+        confidence: Detector confidence between 0 and 1 (0.0 if nothing was detected)
+        is_fallback: True when language is the default because the detector
+            was less than min_confidence sure
+    """
+    
+    language: str
+    confidence: float
+    is_fallback: bool
+
+class AutoStemmer:
+    """
+    Picks the stemmer for each document by detecting its language.
+    
+    Detection uses an offline model restricted to the supported languages.
+    All stemmers it hands out share one cache and admission policy.
+    
+    Args:
+        default: Language used when detection is not confident enough (default: "english")
+        min_confidence: Detector confidence below which the default is used (default: 0.15).
+            Single sentences in closely related languages rarely score above 0.5.
+        cache: As for SnowballStemmer
+        admission: As for SnowballStemmer
+    
+    Raises:
+        ValueError: If the default language or admission policy is not supported,
+            or min_confidence is not between 0 and 1
+    """
+    
+    def __init__(
+        self,
+        default: str = "english",
+        min_confidence: float = 0.15,
+        cache: Union[bool, StemCache, str] = True,
+        admission: Union[str, int] = "always",
+    ) -> None: ...
+    
+    @property
+    def default(self) -> str:
+        """The canonical name of the fallback language."""
+        ...
+    
+    @property
+    def min_confidence(self) -> float: ...
+    
+    def detect(self, text: Union[str, List[str]]) -> LanguageDetection:
+        """Detect the language of a text or token list."""
+        ...
+    
+    def stemmer_for(self, text: Union[str, List[str]]) -> "SnowballStemmer":
+        """
+        Return the stemmer for the detected language of a text or token list.
+        
+        Use it to detect once per document and stem the document's tokens in batches.
+        """
+        ...
+    
+    def stem_words(self, words: List[str]) -> Tuple[List[str], LanguageDetection]:
+        """
+        Detect the language of the tokens, then stem them with it.
+        
+        Returns:
+            The stems, and the detection that chose the algorithm
+        """
+        ...
+    
+    def stem_words_parallel(self, words: List[str]) -> Tuple[List[str], LanguageDetection]:
+        """Like stem_words, stemming in parallel."""
+        ...
+
+class MultiLanguageStemmer:
+    """
+    Stems mixed-script text by routing each token to the algorithm for its script.
+    
+    Tokens in the primary language's script use the primary language. Tokens
+    in other scripts use the fallback for that script. Scripts with a single
+    supported language (Arabic, Cyrillic, Devanagari, Greek, Tamil) route to
+    it by default. Tokens without a route (numbers, symbols, other scripts)
+    are returned unchanged. Cache entries are keyed by the routed algorithm,
+    so they are shared with SnowballStemmers of that language.
+    
+    Args:
+        primary: Language name, code, alias or locale tag of the main language
+        fallbacks: Script name (as in LanguageInfo.script, case-insensitive) to the
+            language its tokens are stemmed with, or None to leave them unstemmed
+        cache: As for SnowballStemmer
+        admission: As for SnowballStemmer
+    
+    Raises:
+        ValueError: If a language, script or admission policy is not supported, a
+            fallback language is not written in its script, or a fallback is given
+            for the primary language's script
+    """
+    
+    def __init__(
+        self,
+        primary: str,
+        fallbacks: Optional[Dict[str, Optional[str]]] = None,
+        cache: Union[bool, StemCache, str] = True,
+        admission: Union[str, int] = "always",
+    ) -> None: ...
+    
+    @property
+    def primary(self) -> str:
+        """The canonical name of the primary language."""
+        ...
+    
+    @property
+    def routes(self) -> Dict[str, str]:
+        """Script name to the canonical name of the language its tokens are stemmed with."""
+        ...
+    
+    @property
+    def cache(self) -> Optional[StemCache]:
+        """The cache used by this stemmer, or None when caching is disabled."""
+        ...
+    
+    def language_for(self, word: str) -> Optional[str]:
+        """The language a token is stemmed with, or None if it is left unchanged."""
+        ...
+    
+    def stem_word(self, word: str) -> str: ...
+    
+    def stem_words(self, words: List[str]) -> List[str]: ...
+    
+    def stem_words_parallel(self, words: List[str]) -> List[str]: ...
 
 class SnowballStemmer:
     """
@@ -8,27 +489,207 @@ class SnowballStemmer:
     
     Supports the following languages:
     - arabic, danish, dutch, english, finnish, french, german, greek,
-    - hungarian, italian, norwegian, portuguese, romanian, russian,
-    - spanish, swedish, tamil, turkish
+    - hindi, hungarian, indonesian, italian, norwegian, portuguese,
+    - romanian, russian, spanish, swedish, tamil, turkish
+    
+    Languages can also be given as ISO 639-1, 639-2/B or 639-3 codes ("en",
+    "ger", "spa"), aliases ("porter2", "norwegian bokmål", "nb") or locale
+    tags ("pt-BR", "en_US").
     
     Args:
-        lang: Language name (case-insensitive)
-        cache: Whether to use caching for better performance with repeated words (default: True)
+        lang: Language name, code, alias or locale tag (case-insensitive)
+        cache: Whether to use caching for better performance with repeated words (default: True).
+            Also accepts a StemCache object or a namespace name to select which cache is used.
+        admission: Which cache misses get stored (default: "always")
+        case: Case mapping applied before stemming: "lower" or "fold" (default: None)
+        normalize: Unicode normalization form applied before stemming: "NFC" or "NFKC" (default: None)
+        orthography: Apply the language's own spelling rules before stemming (default: False)
+        stopwords: Drop stopwords from the results: True for the built-in list, or
+            an iterable of words to use instead (default: False)
+        extra_stopwords: Words added to the stopword list (default: None)
+        protected: Words that are never stemmed, or a file listing them (default: None)
+        overrides: A word -> stem mapping, or a TSV/JSON file of one (default: None)
+        recorder: A StemRecorder that counts the surface forms of each stem (default: None)
+        token_policy: What to do with numbers, URLs, emails, mentions, hashtags and
+            emoji instead of stemming them (default: None)
     
     Raises:
-        ValueError: If the language is not supported
+        ValueError: If the language, admission policy, case or normalization form is not supported
     """
     
-    def __init__(self, lang: str, cache: bool = True) -> None:
+    def __init__(
+        self,
+        lang: str,
+        cache: Union[bool, StemCache, str] = True,
+        admission: Union[str, int] = "always",
+        case: Optional[str] = None,
+        normalize: Optional[str] = None,
+        orthography: bool = False,
+        stopwords: Union[bool, Iterable[str]] = False,
+        extra_stopwords: Optional[Iterable[str]] = None,
+        protected: Union[Iterable[str], str, os.PathLike, None] = None,
+        overrides: Union[Mapping[str, str], str, os.PathLike, None] = None,
+        recorder: Optional[StemRecorder] = None,
+        token_policy: Optional[TokenPolicy] = None,
+    ) -> None:
         """
         Initialize a Snowball stemmer for the specified language.
         
         Args:
-            lang: Language name (case-insensitive)
-            cache: Enable caching for repeated words (default: True)
+            lang: Language name, ISO 639 code, alias or locale tag (case-insensitive)
+            cache: Enable caching for repeated words (default: True). True uses the
+                process-wide cache, a StemCache or namespace name uses that cache,
+                and False disables caching.
+            admission: Cache admission policy, so rare tokens don't crowd out frequent ones:
+                - "always": cache every miss (plain LRU)
+                - "tinylfu": once the cache is full, only cache a word that has been
+                  seen more often recently than the entry it would evict (lookups
+                  answered by the L1 cache count too)
+                - an int N (2 to 15): cache a word once it has been looked up N times
+            case: Case mapping applied to each word before the cache lookup:
+                - None: leave words as they are
+                - "lower": Unicode lowercasing
+                - "fold": full Unicode case folding, which also maps "ß" to "ss"
+            normalize: Unicode normalization applied before the cache lookup, "NFC"
+                or "NFKC", so composed and decomposed spellings share a cache entry
+            orthography: Apply language-specific spelling rules before the cache lookup:
+                - turkish: with `case`, I lowercases to "ı" and "İ" to "i"
+                - greek: accents are dropped and final "ς" is written "σ"
+                - german: "ß" is written "ss", and "ae", "oe", "ue" as "ä", "ö", "ü"
+                  (Snowball's German2 convention)
+                - arabic: vowel marks (tashkeel, including superscript alef and
+                  combining maddah and hamza) and tatweel are removed
+                - dutch: the "ĳ" ligature is written "ij"
+                Other languages have no rules, so the option has no effect for them.
+            stopwords: Whether stem_words, stem_words_parallel, stem_text and stem_texts
+                drop stopwords from their results:
+                - False: keep every word
+                - True: drop words in the built-in list for the language (the Snowball
+                  list where Snowball has one)
+                - an iterable of words: drop these words instead of the built-in list
+                stem_word always returns a stem.
+            extra_stopwords: Words added to the built-in or given stopword list
+            protected: Words returned as they are instead of being stemmed: an
+                iterable of words, or the path of a file with one word per line
+                (or a JSON list, for a `.json` file)
+            overrides: Stems to use instead of the algorithm's, e.g.
+                {"universities": "university"}: a mapping, or the path of a TSV file
+                of word<TAB>stem lines (or a JSON object, for a `.json` file).
+                An override wins over a protected word.
+                Protected words and overrides are normalized like the input and
+                checked before the cache, so they behave the same in every method
+                and never reach a cache shared with other stemmers. Lines starting
+                with "#" are ignored in text files.
+            recorder: Record each word stemmed by any method as a surface form of
+                its stem, cache hits included. Words are recorded as given (after
+                the tokenizer's lowercasing, for stem_text); dropped stopwords are
+                not recorded.
+            token_policy: Keep, drop or normalize tokens that are not words, in
+                every stemming method. stem_text and stem_texts also recognize
+                URLs, emails, mentions, hashtags and emoji in text, which the
+                tokenizer would otherwise split or drop. stem_word returns a
+                dropped token as given.
+        
+        Raises:
+            ValueError: If the language, admission policy, case or normalization form is
+                not supported, stopwords=True for a program without a built-in list,
+                or an overrides file has a line that is not word<TAB>stem
+            TypeError: If stopwords or extra_stopwords is a string rather than an iterable
+            OSError: If a protected or overrides file cannot be read
+        """
+        ...
+    
+    @staticmethod
+    def from_snowball_source(
+        path: Union[str, os.PathLike],
+        cache: Union[bool, StemCache, str] = True,
+        admission: Union[str, int] = "always",
+        case: Optional[str] = None,
+        normalize: Optional[str] = None,
+        orthography: bool = False,
+        stopwords: Union[bool, Iterable[str]] = False,
+        extra_stopwords: Optional[Iterable[str]] = None,
+        protected: Union[Iterable[str], str, os.PathLike, None] = None,
+        overrides: Union[Mapping[str, str], str, os.PathLike, None] = None,
+        recorder: Optional[StemRecorder] = None,
+        token_policy: Optional[TokenPolicy] = None,
+    ) -> "SnowballStemmer":
+        """
+        Create a stemmer that runs the Snowball program in a `.sbl` file.
+        
+        The program is interpreted at runtime, so modified algorithms can be used
+        without rebuilding the extension. Loading the same source twice shares
+        cached stems; they are not written by save_cache().
+        
+        Args:
+            path: Path to the Snowball source, which must define a `stem` external
+            cache: As for the constructor
+            admission: As for the constructor
+            case: As for the constructor
+            normalize: As for the constructor
+            orthography: As for the constructor
+            stopwords: As for the constructor; there is no built-in list, so True
+                raises ValueError
+            extra_stopwords: As for the constructor
+            protected: As for the constructor
+            overrides: As for the constructor
+            recorder: As for the constructor
+            token_policy: As for the constructor
+        
+        Returns:
+            A stemmer whose `language` is the file name without its extension
         
         Raises:
-            ValueError: If the language is not supported
+            OSError: If the file cannot be read
+            ValueError: If the source is not a valid Snowball program, or uses
+                `reverse`, which is not supported
+        """
+        ...
+    
+    @property
+    def language(self) -> str:
+        """
+        The canonical name of the language, e.g. "portuguese" for "pt-BR", or
+        the file name of a program loaded with from_snowball_source().
+        """
+        ...
+    
+    @property
+    def cache(self) -> Optional[StemCache]:
+        """The cache used by this stemmer, or None when caching is disabled."""
+        ...
+    
+    @property
+    def recorder(self) -> Optional[StemRecorder]:
+        """The recorder given to the constructor, or None."""
+        ...
+    
+    @property
+    def stopwords(self) -> Set[str]:
+        """
+        The stopword list: the built-in list or the one given to the constructor,
+        plus any extra_stopwords. Empty for programs without a list.
+        """
+        ...
+    
+    def is_stopword(self, word: str) -> bool:
+        """
+        Check whether a word is a stopword, as given, lowercased, or normalized
+        as the stemmer would normalize it.
+        """
+        ...
+    
+    def filter_stopwords(self, words: List[str]) -> List[str]:
+        """
+        Remove stopwords from a list of words without stemming them.
+        
+        This works whether or not the stemmer was created with `stopwords`.
+        
+        Args:
+            words: The words to filter
+        
+        Returns:
+            The words that are not stopwords, in their original order
         """
         ...
     
@@ -59,6 +720,61 @@ class SnowballStemmer:
         """
         ...
     
+    def warm_cache(self, words: List[str], background: bool = False) -> Union[int, CacheWarmup]:
+        """
+        Stem a vocabulary in parallel and store the results in this stemmer's cache.
+        
+        Words are prepared the way stemming would prepare them: stopwords and words
+        the token policy drops or keeps verbatim are skipped, as are words with an
+        override, and the rest are normalized before they are stemmed.
+        
+        A background warm-up that panics raises RuntimeError from CacheWarmup.wait
+        in debug builds; release builds abort on panic, ending the process.
+        
+        Args:
+            words: Words expected in upcoming requests
+            background: Return right away and fill the cache on a separate thread
+        
+        Returns:
+            The number of words stemmed, not counting skipped words, or a CacheWarmup
+            handle when background is True
+        
+        Raises:
+            ValueError: If caching is disabled for this stemmer
+        """
+        ...
+    
+    def warm_cache_from_file(
+        self, path: Union[str, os.PathLike], background: bool = False
+    ) -> Union[int, CacheWarmup]:
+        """
+        Like warm_cache, reading the vocabulary from a text file.
+        
+        The file has one word per line. Anything after the first whitespace on a
+        line (such as a frequency column) is ignored, and so are blank lines.
+        
+        Args:
+            path: Vocabulary file
+            background: Return right away and fill the cache on a separate thread
+        
+        Returns:
+            The number of words stemmed, or a CacheWarmup handle when background is True
+        
+        Raises:
+            ValueError: If caching is disabled for this stemmer
+            OSError: If the file cannot be read (raised by CacheWarmup.wait in the background)
+        """
+        ...
+    
+    def cache_stats(self) -> CacheStats:
+        """
+        Return the counters of the cache used by this stemmer.
+        
+        Returns:
+            A CacheStats snapshot, all zero when caching is disabled
+        """
+        ...
+    
     def stem_words_parallel(self, inputs: List[str]) -> List[str]:
         """
         Stem a list of words in parallel using multiple threads.
@@ -73,3 +789,199 @@ class SnowballStemmer:
             List of stemmed words in the same order as input
         """
         ...
+    
+    def stem_text(self, text: str, tokenizer: Optional[Tokenizer] = None) -> List[str]:
+        """
+        Split text into words and stem them. The GIL is released while this runs.
+        
+        Args:
+            text: Text to stem
+            tokenizer: How to split the text (default: Tokenizer())
+        
+        Returns:
+            The stemmed tokens in text order; numbers are returned unstemmed
+        """
+        ...
+    
+    def stem_texts(self, texts: List[str], tokenizer: Optional[Tokenizer] = None) -> List[List[str]]:
+        """
+        Like stem_text() for many texts, processed in parallel.
+        
+        Args:
+            texts: Texts to stem
+            tokenizer: How to split the texts (default: Tokenizer())
+        
+        Returns:
+            The stemmed tokens of each text, in the same order as texts
+        """
+        ...
+    
+    def stem_text_with_offsets(
+        self, text: str, tokenizer: Optional[Tokenizer] = None
+    ) -> List[StemmedToken]:
+        """
+        Like stem_text(), with each token's surface form and offsets, for
+        highlighting matches in the original text.
+        
+        Args:
+            text: Text to stem
+            tokenizer: How to split the text (default: Tokenizer())
+        
+        Returns:
+            The stemmed tokens in text order
+        """
+        ...
+    
+    def stem_texts_with_offsets(
+        self, texts: List[str], tokenizer: Optional[Tokenizer] = None
+    ) -> List[List[StemmedToken]]:
+        """
+        Like stem_text_with_offsets() for many texts, processed in parallel.
+        
+        Args:
+            texts: Texts to stem
+            tokenizer: How to split the texts (default: Tokenizer())
+        
+        Returns:
+            The stemmed tokens of each text, in the same order as texts
+        """
+        ...
+    
+    def __reduce__(self) -> Tuple[Any, Tuple[Any, ...]]:
+        """
+        Pickle support, so stemmers can be sent to multiprocessing, joblib or
+        Spark workers. The stemmer is rebuilt from its language (or Snowball
+        source), cache and admission policy. Stemmers sharing a cache still share
+        one after being unpickled together.
+        """
+        ...
+    
+    def __eq__(self, other: object) -> bool:
+        """Equal when running the same algorithm into the same cache with the same admission policy."""
+        ...
+    
+    def __hash__(self) -> int: ...
+    
+    def __repr__(self) -> str: ...
+
+
+def set_cache_limits(
+    max_entries: Optional[int] = 100_000, max_bytes: Optional[int] = None
+) -> None:
+    """
+    Bound the shared stem cache.
+    
+    The cache evicts least-recently-used entries once either limit is reached.
+    Limits are split across the cache's internal shards, and small limits use
+    fewer shards, so the cache never holds more than max_entries words. A
+    limit of 0 stops caching. Lowering a limit evicts entries immediately.
+    
+    Args:
+        max_entries: Maximum number of cached words, or None for no limit (default: 100000)
+        max_bytes: Approximate maximum memory used by cached entries, or None for no limit
+    """
+    ...
+
+def get_cache_limits() -> Tuple[Optional[int], Optional[int]]:
+    """
+    Return the current (max_entries, max_bytes) limits of the shared stem cache.
+    
+    Returns:
+        Tuple of the entry and byte limits; None means the limit is disabled
+    """
+    ...
+
+def cache_stats() -> CacheStats:
+    """
+    Return the counters of the shared stem cache.
+    
+    Returns:
+        A CacheStats snapshot
+    """
+    ...
+
+def clear_cache(lang: Optional[str] = None) -> None:
+    """
+    Remove entries from the shared stem cache and release their memory.
+    
+    Args:
+        lang: Only remove stems of this language (case-insensitive); all languages if None
+    
+    Raises:
+        ValueError: If the language is not supported
+    """
+    ...
+
+def shrink_cache() -> None:
+    """
+    Release memory the shared stem cache no longer needs.
+    
+    Evictions and clears leave spare room in the cache's hash tables. This
+    rebuilds them at the size of the remaining entries, keeping their order.
+    """
+    ...
+
+def save_cache(path: Union[str, os.PathLike]) -> int:
+    """
+    Write the shared stem cache to a compact binary file.
+    
+    The file records the format version, the rust-stemmers version and the
+    languages it contains, with a fingerprint of the source of each algorithm
+    implemented in this package, so it can only be loaded by a build whose
+    algorithms produce the same stems.
+    An existing file at `path` is replaced atomically.
+    
+    Args:
+        path: Destination file
+    
+    Returns:
+        The number of entries written
+    """
+    ...
+
+def load_cache(path: Union[str, os.PathLike]) -> int:
+    """
+    Fill the shared stem cache from a file written by save_cache.
+    
+    Entries are added to what is already cached, subject to the cache limits.
+    
+    Args:
+        path: File written by save_cache
+    
+    Returns:
+        The number of entries read
+    
+    Raises:
+        ValueError: If the file is not a stem cache or comes from an incompatible build
+        OSError: If the file cannot be read
+    """
+    ...
+
+def supported_languages() -> List[LanguageInfo]:
+    """
+    List the languages this build can stem, in alphabetical order.
+    
+    Returns:
+        One LanguageInfo per supported language
+    """
+    ...
+
+def detect_language(
+    text: Union[str, List[str]], default: str = "english", min_confidence: float = 0.15
+) -> LanguageDetection:
+    """
+    Detect the language of a text or token list among the supported languages.
+    
+    Args:
+        text: The document, as a string or a list of tokens
+        default: Language reported when detection is not confident enough
+        min_confidence: Detector confidence below which the default is reported
+    
+    Returns:
+        The detected language and confidence
+    
+    Raises:
+        ValueError: If the default language is not supported or min_confidence
+            is not between 0 and 1
+    """
+    ...
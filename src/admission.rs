@@ -0,0 +1,138 @@
+// Cache admission: decides whether a freshly stemmed word is worth a cache
+// slot, so long tails of typos, IDs and hapaxes don't push out frequent words.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Admission {
+    // Cache every miss (plain LRU)
+    Always,
+    // Cache a word once it has been looked up this many times
+    MinSightings(u8),
+    // Once the shard is full, only cache a word if it has been seen more
+    // often than the entry it would evict
+    TinyLfu,
+}
+
+impl Admission {
+    #[inline(always)]
+    pub fn tracks_frequency(self) -> bool {
+        self != Admission::Always
+    }
+}
+
+// What the `admission` argument of `SnowballStemmer` accepts
+#[derive(FromPyObject, IntoPyObject)]
+pub enum AdmissionArg {
+    Sightings(u32),
+    Policy(String),
+}
+
+impl AdmissionArg {
+    pub fn resolve(self) -> PyResult<Admission> {
+        match self {
+            AdmissionArg::Sightings(1) => Ok(Admission::Always),
+            AdmissionArg::Sightings(n @ 2..=15) => Ok(Admission::MinSightings(n as u8)),
+            AdmissionArg::Sightings(n) => Err(PyValueError::new_err(format!(
+                "Admission sightings must be between 1 and {}, got {}",
+                MAX_COUNT, n
+            ))),
+            AdmissionArg::Policy(policy) => match policy.to_lowercase().as_str() {
+                "always" | "lru" => Ok(Admission::Always),
+                "tinylfu" | "tiny-lfu" => Ok(Admission::TinyLfu),
+                _ => Err(PyValueError::new_err(format!(
+                    "Unsupported admission policy: {}",
+                    policy
+                ))),
+            },
+        }
+    }
+}
+
+// The argument that resolves to `admission`, for pickling and repr
+impl From<Admission> for AdmissionArg {
+    fn from(admission: Admission) -> Self {
+        match admission {
+            Admission::Always => AdmissionArg::Policy("always".to_owned()),
+            Admission::MinSightings(n) => AdmissionArg::Sightings(u32::from(n)),
+            Admission::TinyLfu => AdmissionArg::Policy("tinylfu".to_owned()),
+        }
+    }
+}
+
+const SKETCH_DEPTH: usize = 4;
+const SKETCH_WIDTH: usize = 4096;
+// Counters saturate at 15 like the 4-bit counters of TinyLFU
+const MAX_COUNT: u8 = 15;
+// Odd multipliers giving each sketch row an independent index
+const ROW_SEEDS: [u64; SKETCH_DEPTH] = [
+    0x9e37_79b9_7f4a_7c15,
+    0xc2b2_ae3d_27d4_eb4f,
+    0x1656_67b1_9e37_79f9,
+    0x85eb_ca77_c2b2_ae63,
+];
+
+const RESET_AFTER: usize = 10 * SKETCH_WIDTH;
+
+// Count-min sketch of recent lookup frequencies. All counters are halved
+// after every `RESET_AFTER` increments, so past popularity fades.
+//
+// Counters are updated without a lock, so lookups answered by an L1 cache
+// are counted too. Racing increments of one counter can lose a count, which
+// only lowers an estimate a little; saturated counters are never written, so
+// hot words don't bounce a cache line between threads.
+pub struct FrequencySketch {
+    counters: Box<[AtomicU8]>,
+    additions: AtomicUsize,
+}
+
+impl FrequencySketch {
+    pub fn new() -> Self {
+        FrequencySketch {
+            counters: (0..SKETCH_DEPTH * SKETCH_WIDTH)
+                .map(|_| AtomicU8::new(0))
+                .collect(),
+            additions: AtomicUsize::new(0),
+        }
+    }
+
+    #[inline(always)]
+    fn index(hash: u64, row: usize) -> usize {
+        let mixed = hash.wrapping_mul(ROW_SEEDS[row]);
+        row * SKETCH_WIDTH + (mixed >> 32) as usize % SKETCH_WIDTH
+    }
+
+    pub fn increment(&self, hash: u64) {
+        let mut added = false;
+        for row in 0..SKETCH_DEPTH {
+            let counter = &self.counters[Self::index(hash, row)];
+            let count = counter.load(Ordering::Relaxed);
+            if count < MAX_COUNT {
+                counter.store(count + 1, Ordering::Relaxed);
+                added = true;
+            }
+        }
+        if added && self.additions.fetch_add(1, Ordering::Relaxed) + 1 == RESET_AFTER {
+            for counter in self.counters.iter() {
+                counter.store(counter.load(Ordering::Relaxed) >> 1, Ordering::Relaxed);
+            }
+            self.additions.fetch_sub(RESET_AFTER / 2, Ordering::Relaxed);
+        }
+    }
+
+    pub fn estimate(&self, hash: u64) -> u8 {
+        (0..SKETCH_DEPTH)
+            .map(|row| self.counters[Self::index(hash, row)].load(Ordering::Relaxed))
+            .min()
+            .unwrap_or(0)
+    }
+
+    pub fn reset(&self) {
+        for counter in self.counters.iter() {
+            counter.store(0, Ordering::Relaxed);
+        }
+        self.additions.store(0, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,181 @@
+// Port of the Snowball Hindi stemmer, an implementation of "A Lightweight
+// Stemmer for Hindi" (Ananthakrishnan Ramanathan and Durgesh D. Rao). The
+// longest matching suffix is removed, as long as at least one character of
+// the word is left.
+//
+// The paper lists suffixes starting with a vowel sign (matra); each one also
+// appears with the independent vowel, and suffixes with an anusvara (ं) also
+// appear with a chandrabindu (ँ), since both spellings are common.
+
+use std::borrow::Cow;
+
+// Longest first, so the first match is the one to remove
+const SUFFIXES: [&str; 156] = [
+    "ाएंगी",
+    "ाएँगी",
+    "आएंगी",
+    "आएँगी",
+    "ाएंगे",
+    "ाएँगे",
+    "आएंगे",
+    "आएँगे",
+    "ाऊंगी",
+    "ाऊँगी",
+    "आऊंगी",
+    "आऊँगी",
+    "ाऊंगा",
+    "ाऊँगा",
+    "आऊंगा",
+    "आऊँगा",
+    "ाइयाँ",
+    "ाइयां",
+    "आइयाँ",
+    "आइयां",
+    "ाइयों",
+    "ाइयोँ",
+    "आइयों",
+    "आइयोँ",
+    "ाएगी",
+    "आएगी",
+    "ाएगा",
+    "आएगा",
+    "ाओगी",
+    "आओगी",
+    "ाओगे",
+    "आओगे",
+    "एंगी",
+    "एँगी",
+    "ेंगी",
+    "ेँगी",
+    "एंगे",
+    "एँगे",
+    "ेंगे",
+    "ेँगे",
+    "ूंगी",
+    "ूँगी",
+    "ऊंगी",
+    "ऊँगी",
+    "ूंगा",
+    "ूँगा",
+    "ऊंगा",
+    "ऊँगा",
+    "ातीं",
+    "ातीँ",
+    "आतीं",
+    "आतीँ",
+    "नाओं",
+    "नाओँ",
+    "नाएं",
+    "नाएँ",
+    "ताओं",
+    "ताओँ",
+    "ताएं",
+    "ताएँ",
+    "ियाँ",
+    "ियां",
+    "इयाँ",
+    "इयां",
+    "ियों",
+    "ियोँ",
+    "इयों",
+    "इयोँ",
+    "ाकर",
+    "आकर",
+    "ाइए",
+    "आइए",
+    "ाईं",
+    "ाईँ",
+    "आईं",
+    "आईँ",
+    "ाया",
+    "आया",
+    "ेगी",
+    "एगी",
+    "ेगा",
+    "एगा",
+    "ोगी",
+    "ओगी",
+    "ोगे",
+    "ओगे",
+    "ाने",
+    "आने",
+    "ाना",
+    "आना",
+    "ाते",
+    "आते",
+    "ाती",
+    "आती",
+    "ाता",
+    "आता",
+    "तीं",
+    "तीँ",
+    "ाओं",
+    "ाओँ",
+    "आओं",
+    "आओँ",
+    "ाएं",
+    "ाएँ",
+    "आएं",
+    "आएँ",
+    "ुओं",
+    "ुओँ",
+    "उओं",
+    "उओँ",
+    "ुएं",
+    "ुएँ",
+    "उएं",
+    "उएँ",
+    "ुआं",
+    "ुआँ",
+    "उआं",
+    "उआँ",
+    "कर",
+    "ाओ",
+    "आओ",
+    "िए",
+    "इए",
+    "ाई",
+    "आई",
+    "ाए",
+    "आए",
+    "ने",
+    "नी",
+    "ना",
+    "ते",
+    "ीं",
+    "ीँ",
+    "ईं",
+    "ईँ",
+    "ती",
+    "ता",
+    "ाँ",
+    "ां",
+    "आँ",
+    "आं",
+    "ों",
+    "ोँ",
+    "ओं",
+    "ओँ",
+    "ें",
+    "ेँ",
+    "एं",
+    "एँ",
+    "ो",
+    "े",
+    "ू",
+    "ु",
+    "ी",
+    "ि",
+    "ा",
+];
+
+pub fn stem(word: &str) -> Cow<'_, str> {
+    let Some(first) = word.chars().next() else {
+        return Cow::Borrowed(word);
+    };
+    let rest = &word[first.len_utf8()..];
+    match SUFFIXES.iter().find(|suffix| rest.ends_with(*suffix)) {
+        Some(suffix) => Cow::Owned(word[..word.len() - suffix.len()].to_owned()),
+        None => Cow::Borrowed(word),
+    }
+}
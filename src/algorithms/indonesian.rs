@@ -0,0 +1,191 @@
+// Port of the Snowball Indonesian stemmer, itself an implementation of
+// "A Study of Stemming Effects on Information Retrieval in Bahasa Indonesia"
+// (Fadillah Z. Tala). It strips, in order, particles, possessive pronouns,
+// first-order prefixes, suffixes and second-order prefixes, and stops as soon
+// as the word is down to two vowels.
+
+use std::borrow::Cow;
+
+// Which prefix was removed; restricts which suffixes may follow it
+const PREFIX_NONE: u8 = 0;
+// di-, me-, meng-, men-, meny-, mem-, ter-
+const PREFIX_ME: u8 = 1;
+// pe-, per-
+const PREFIX_PE: u8 = 2;
+// ke-, peng-, pen-, peny-, pem-
+const PREFIX_PENG: u8 = 3;
+// be-, ber-
+const PREFIX_BER: u8 = 4;
+
+fn is_vowel(c: char) -> bool {
+    matches!(c, 'a' | 'e' | 'i' | 'o' | 'u')
+}
+
+struct Word {
+    text: String,
+    // Vowels left in the word
+    measure: usize,
+    prefix: u8,
+}
+
+impl Word {
+    fn vowel_at(&self, index: usize) -> bool {
+        self.text[index..].chars().next().is_some_and(is_vowel)
+    }
+
+    // be- + consonant + -er, as in bekerja -> kerja
+    fn be_ker(&self) -> bool {
+        self.text.starts_with("be")
+            && self.text[2..]
+                .chars()
+                .next()
+                .is_some_and(|c| !is_vowel(c) && self.text[2 + c.len_utf8()..].starts_with("er"))
+    }
+
+    fn remove_suffix_of(&mut self, len: usize) -> bool {
+        self.text.truncate(self.text.len() - len);
+        self.measure -= 1;
+        true
+    }
+
+    fn replace_prefix(&mut self, len: usize, replacement: &str) {
+        self.text.replace_range(..len, replacement);
+        self.measure -= 1;
+    }
+
+    // -kah, -lah, -pun
+    fn remove_particle(&mut self) -> bool {
+        let found = ["kah", "lah", "pun"]
+            .iter()
+            .any(|particle| self.text.ends_with(particle));
+        found && self.remove_suffix_of(3)
+    }
+
+    // -ku, -mu, -nya
+    fn remove_possessive_pronoun(&mut self) -> bool {
+        let len = if self.text.ends_with("nya") {
+            3
+        } else if self.text.ends_with("ku") || self.text.ends_with("mu") {
+            2
+        } else {
+            return false;
+        };
+        self.remove_suffix_of(len)
+    }
+
+    // -kan, -an, -i; a suffix whose condition fails falls back to the
+    // next shorter one, as with a Snowball `among`
+    fn remove_suffix(&mut self) -> bool {
+        let len = if self.text.ends_with("kan")
+            && self.prefix != PREFIX_PENG
+            && self.prefix != PREFIX_PE
+        {
+            3
+        } else if self.text.ends_with("an") && self.prefix != PREFIX_ME {
+            2
+        } else if self.text.ends_with('i') && self.prefix <= PREFIX_PE && !self.text.ends_with("si")
+        {
+            1
+        } else {
+            return false;
+        };
+        self.remove_suffix_of(len)
+    }
+
+    fn remove_first_order_prefix(&mut self) -> bool {
+        let text = self.text.as_str();
+        let starts = |prefix: &str| text.starts_with(prefix);
+        let (len, replacement, prefix) = if starts("meny") && self.vowel_at(4) {
+            (4, "s", PREFIX_ME)
+        } else if starts("peny") && self.vowel_at(4) {
+            (4, "s", PREFIX_PENG)
+        } else if starts("meng") {
+            (4, "", PREFIX_ME)
+        } else if starts("peng") {
+            (4, "", PREFIX_PENG)
+        } else if starts("mem") {
+            // mem- + vowel comes from a root starting with p: memukul -> pukul
+            (3, if self.vowel_at(3) { "p" } else { "" }, PREFIX_ME)
+        } else if starts("pem") {
+            (3, if self.vowel_at(3) { "p" } else { "" }, PREFIX_PENG)
+        } else if starts("men") || starts("ter") {
+            (3, "", PREFIX_ME)
+        } else if starts("pen") {
+            (3, "", PREFIX_PENG)
+        } else if starts("di") || starts("me") {
+            (2, "", PREFIX_ME)
+        } else if starts("ke") {
+            (2, "", PREFIX_PENG)
+        } else {
+            return false;
+        };
+        self.replace_prefix(len, replacement);
+        self.prefix = prefix;
+        true
+    }
+
+    fn remove_second_order_prefix(&mut self) -> bool {
+        let text = self.text.as_str();
+        let (len, prefix) = if text.starts_with("belajar") {
+            // belajar -> ajar
+            (3, PREFIX_BER)
+        } else if text.starts_with("pelajar") {
+            // pelajar -> ajar, without restricting the suffixes
+            (3, self.prefix)
+        } else if text.starts_with("ber") {
+            (3, PREFIX_BER)
+        } else if text.starts_with("per") {
+            (3, PREFIX_PE)
+        } else if self.be_ker() {
+            (2, PREFIX_BER)
+        } else if text.starts_with("pe") {
+            (2, PREFIX_PE)
+        } else {
+            return false;
+        };
+        self.replace_prefix(len, "");
+        self.prefix = prefix;
+        true
+    }
+
+    fn stem(&mut self) {
+        if self.measure <= 2 {
+            return;
+        }
+        self.remove_particle();
+        if self.measure <= 2 {
+            return;
+        }
+        self.remove_possessive_pronoun();
+        if self.measure <= 2 {
+            return;
+        }
+        if self.remove_first_order_prefix() {
+            // The second-order prefix only goes if a suffix did too
+            let removed_suffix = self.measure > 2 && self.remove_suffix();
+            if removed_suffix && self.measure > 2 {
+                self.remove_second_order_prefix();
+            }
+        } else {
+            self.remove_second_order_prefix();
+            if self.measure > 2 {
+                self.remove_suffix();
+            }
+        }
+    }
+}
+
+pub fn stem(word: &str) -> Cow<'_, str> {
+    let mut stemmed = Word {
+        text: word.to_owned(),
+        measure: word.chars().filter(|&c| is_vowel(c)).count(),
+        prefix: PREFIX_NONE,
+    };
+    stemmed.stem();
+    // Every rule shortens the word
+    if stemmed.text.len() == word.len() {
+        Cow::Borrowed(word)
+    } else {
+        Cow::Owned(stemmed.text)
+    }
+}
@@ -0,0 +1,166 @@
+// Stemming algorithms: the Snowball stemmers bundled with rust-stemmers, plus
+// native ports of newer upstream Snowball algorithms the crate doesn't ship,
+// plus Snowball programs loaded from source at runtime.
+
+mod hindi;
+mod indonesian;
+
+use crate::snowball::Program;
+use std::borrow::Cow;
+use std::sync::{Arc, Mutex};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Algorithm {
+    Arabic,
+    Danish,
+    Dutch,
+    English,
+    Finnish,
+    French,
+    German,
+    Greek,
+    Hindi,
+    Hungarian,
+    Indonesian,
+    Italian,
+    Norwegian,
+    Portuguese,
+    Romanian,
+    Russian,
+    Spanish,
+    Swedish,
+    Tamil,
+    Turkish,
+    // A program loaded with `load_snowball`, by cache discriminant
+    Custom(u8),
+}
+
+// Cache discriminants of loaded programs start here, leaving room for more
+// built-in algorithms
+pub const FIRST_CUSTOM_ID: u8 = 32;
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+const fn fnv1a(mut hash: u64, text: &str) -> u64 {
+    let bytes = text.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        hash = (hash ^ bytes[i] as u64).wrapping_mul(FNV_PRIME);
+        i += 1;
+    }
+    hash
+}
+
+// Identifies the version of an algorithm implemented in this crate by a hash
+// of its source, so cache snapshots notice when its stems may have changed.
+// `None` for the rust-stemmers algorithms, which its version covers, and
+// loaded programs.
+pub fn fingerprint(algorithm: Algorithm) -> Option<u64> {
+    const HINDI: u64 = fnv1a(FNV_OFFSET, include_str!("hindi.rs"));
+    const INDONESIAN: u64 = fnv1a(FNV_OFFSET, include_str!("indonesian.rs"));
+    match algorithm {
+        Algorithm::Hindi => Some(HINDI),
+        Algorithm::Indonesian => Some(INDONESIAN),
+        _ => None,
+    }
+}
+
+struct CustomProgram {
+    name: String,
+    source: String,
+    program: Arc<Program>,
+}
+
+// Programs loaded so far, indexed by discriminant - FIRST_CUSTOM_ID. They
+// are never unloaded, as their stems may still be cached.
+static CUSTOM_PROGRAMS: Mutex<Vec<CustomProgram>> = Mutex::new(Vec::new());
+
+// Compile a Snowball program and give it a cache discriminant. Loading the
+// same source again reuses the first program, and so its cached stems.
+pub fn load_snowball(name: &str, source: String) -> Result<Algorithm, String> {
+    let mut programs = CUSTOM_PROGRAMS.lock().unwrap();
+    let index = match programs.iter().position(|custom| custom.source == source) {
+        Some(index) => index,
+        None => {
+            if programs.len() > usize::from(u8::MAX - FIRST_CUSTOM_ID) {
+                return Err(format!(
+                    "at most {} Snowball programs can be loaded",
+                    programs.len()
+                ));
+            }
+            let program = Arc::new(Program::compile(&source)?);
+            programs.push(CustomProgram {
+                name: name.to_owned(),
+                source,
+                program,
+            });
+            programs.len() - 1
+        }
+    };
+    Ok(Algorithm::Custom(FIRST_CUSTOM_ID + index as u8))
+}
+
+// Name a loaded program was first loaded under
+pub fn custom_name(id: u8) -> String {
+    CUSTOM_PROGRAMS.lock().unwrap()[usize::from(id - FIRST_CUSTOM_ID)]
+        .name
+        .clone()
+}
+
+// Name and source of a loaded program, to load it again in another process
+pub fn custom_source(id: u8) -> (String, String) {
+    let programs = CUSTOM_PROGRAMS.lock().unwrap();
+    let custom = &programs[usize::from(id - FIRST_CUSTOM_ID)];
+    (custom.name.clone(), custom.source.clone())
+}
+
+pub enum Stemmer {
+    Snowball(rust_stemmers::Stemmer),
+    Native(fn(&str) -> Cow<'_, str>),
+    Interpreted(Arc<Program>),
+}
+
+impl Stemmer {
+    pub fn create(algorithm: Algorithm) -> Self {
+        use rust_stemmers::Algorithm as Snowball;
+        let snowball = match algorithm {
+            Algorithm::Hindi => return Stemmer::Native(hindi::stem),
+            Algorithm::Indonesian => return Stemmer::Native(indonesian::stem),
+            Algorithm::Custom(id) => {
+                let programs = CUSTOM_PROGRAMS.lock().unwrap();
+                let custom = &programs[usize::from(id - FIRST_CUSTOM_ID)];
+                return Stemmer::Interpreted(Arc::clone(&custom.program));
+            }
+            Algorithm::Arabic => Snowball::Arabic,
+            Algorithm::Danish => Snowball::Danish,
+            Algorithm::Dutch => Snowball::Dutch,
+            Algorithm::English => Snowball::English,
+            Algorithm::Finnish => Snowball::Finnish,
+            Algorithm::French => Snowball::French,
+            Algorithm::German => Snowball::German,
+            Algorithm::Greek => Snowball::Greek,
+            Algorithm::Hungarian => Snowball::Hungarian,
+            Algorithm::Italian => Snowball::Italian,
+            Algorithm::Norwegian => Snowball::Norwegian,
+            Algorithm::Portuguese => Snowball::Portuguese,
+            Algorithm::Romanian => Snowball::Romanian,
+            Algorithm::Russian => Snowball::Russian,
+            Algorithm::Spanish => Snowball::Spanish,
+            Algorithm::Swedish => Snowball::Swedish,
+            Algorithm::Tamil => Snowball::Tamil,
+            Algorithm::Turkish => Snowball::Turkish,
+        };
+        Stemmer::Snowball(rust_stemmers::Stemmer::create(snowball))
+    }
+
+    // Like rust-stemmers, expects lowercase input
+    #[inline(always)]
+    pub fn stem<'a>(&self, word: &'a str) -> Cow<'a, str> {
+        match self {
+            Stemmer::Snowball(stemmer) => stemmer.stem(word),
+            Stemmer::Native(stem) => stem(word),
+            Stemmer::Interpreted(program) => program.stem(word),
+        }
+    }
+}
@@ -0,0 +1,653 @@
+use crate::admission::{Admission, FrequencySketch};
+use crate::algorithms::Stemmer;
+use crate::l1;
+use foldhash::fast::RandomState;
+use lru::LruCache;
+use pyo3::prelude::*;
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::mem::size_of;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard, OnceLock, PoisonError};
+
+// (algorithm discriminant, word) -> stem
+pub type CacheKey = (u8, String);
+
+// Lookups go through `dyn KeyView` so a borrowed `(u8, &str)` can probe the
+// map without allocating an owned key; both sides hash and compare the same
+// `(u8, &str)` view.
+pub trait KeyView {
+    fn view(&self) -> (u8, &str);
+}
+
+impl KeyView for CacheKey {
+    #[inline(always)]
+    fn view(&self) -> (u8, &str) {
+        (self.0, &self.1)
+    }
+}
+
+impl KeyView for (u8, &str) {
+    #[inline(always)]
+    fn view(&self) -> (u8, &str) {
+        *self
+    }
+}
+
+impl Hash for dyn KeyView + '_ {
+    #[inline(always)]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.view().hash(state);
+    }
+}
+
+impl PartialEq for dyn KeyView + '_ {
+    #[inline(always)]
+    fn eq(&self, other: &Self) -> bool {
+        self.view() == other.view()
+    }
+}
+
+impl Eq for dyn KeyView + '_ {}
+
+impl<'a> Borrow<dyn KeyView + 'a> for CacheKey {
+    #[inline(always)]
+    fn borrow(&self) -> &(dyn KeyView + 'a) {
+        self
+    }
+}
+
+pub const DEFAULT_MAX_ENTRIES: usize = 100_000;
+pub const DEFAULT_L1_CAPACITY: usize = 256;
+const MIN_SHARDS: usize = 32;
+
+// Overrides the default shard count, e.g. for machines with more cores than
+// the process gets to use
+const SHARDS_ENV_VAR: &str = "PY_RUST_STEMMERS_SHARDS";
+
+// Enough shards that many-core machines don't queue on the same lock
+pub fn default_shard_count() -> usize {
+    if let Some(shards) = std::env::var(SHARDS_ENV_VAR)
+        .ok()
+        .and_then(|value| value.trim().parse::<usize>().ok())
+        .filter(|&shards| shards > 0)
+    {
+        return shards;
+    }
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    (threads * 4).next_power_of_two().max(MIN_SHARDS)
+}
+
+static NEXT_CACHE_ID: AtomicU64 = AtomicU64::new(0);
+
+// Rough per-entry bookkeeping cost: key and value headers plus the LRU node
+// (two list pointers) and the hash table slot pointing at it.
+const ENTRY_OVERHEAD: usize = size_of::<CacheKey>() + size_of::<String>() + 3 * size_of::<usize>();
+
+#[inline(always)]
+const fn entry_size(word_len: usize, stem_len: usize) -> usize {
+    ENTRY_OVERHEAD + word_len + stem_len
+}
+
+// Small limits are spread over fewer shards, so that each shard in use has
+// room for a useful number of entries (of a typical word length)
+const MIN_SHARD_ENTRIES: usize = 16;
+const MIN_SHARD_BYTES: usize = MIN_SHARD_ENTRIES * entry_size(8, 6);
+
+fn active_shards(shards: usize, max_entries: usize, max_bytes: usize) -> usize {
+    shards
+        .min(max_entries.div_ceil(MIN_SHARD_ENTRIES))
+        .min(max_bytes.div_ceil(MIN_SHARD_BYTES))
+        .max(1)
+}
+
+// Share of a total limit for one of `active` shards. The remainder goes to
+// the first shards, so the shares add up to the limit exactly.
+#[inline(always)]
+fn shard_share(limit: usize, index: usize, active: usize) -> usize {
+    if limit == usize::MAX {
+        limit
+    } else {
+        limit / active + usize::from(index < limit % active)
+    }
+}
+
+// Limits are stored as totals; `usize::MAX` means "no limit"
+#[inline(always)]
+fn encode_limit(limit: Option<usize>) -> usize {
+    limit.unwrap_or(usize::MAX)
+}
+
+#[inline(always)]
+fn decode_limit(limit: usize) -> Option<usize> {
+    (limit != usize::MAX).then_some(limit)
+}
+
+// Snapshot of cache counters, summed over all shards
+#[pyclass(module = "py_rust_stemmers_tuned", frozen, get_all)]
+#[derive(Clone, Default)]
+pub struct CacheStats {
+    // Includes `l1_hits`
+    pub hits: u64,
+    // Hits answered by a thread-local L1 cache without touching the shards
+    pub l1_hits: u64,
+    pub misses: u64,
+    pub inserts: u64,
+    pub evictions: u64,
+    // Misses the admission policy decided not to cache
+    pub rejections: u64,
+    pub entries: usize,
+    // Keyed by algorithm discriminant, languages without entries are omitted
+    pub entries_per_language: HashMap<u8, usize>,
+    pub memory_bytes: usize,
+}
+
+#[pymethods]
+impl CacheStats {
+    #[getter]
+    fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "CacheStats(hits={}, misses={}, inserts={}, evictions={}, rejections={}, entries={}, memory_bytes={})",
+            self.hits,
+            self.misses,
+            self.inserts,
+            self.evictions,
+            self.rejections,
+            self.entries,
+            self.memory_bytes
+        )
+    }
+}
+
+// Counters live inside the shard and are only touched while its lock is
+// already held, so tracking them adds no extra contention
+#[derive(Default)]
+struct ShardCounters {
+    hits: u64,
+    misses: u64,
+    inserts: u64,
+    evictions: u64,
+    rejections: u64,
+    // Indexed by algorithm discriminant
+    entries_per_language: Vec<usize>,
+}
+
+impl ShardCounters {
+    #[inline(always)]
+    fn language_entries(&mut self, lang: u8) -> &mut usize {
+        let index = lang as usize;
+        if index >= self.entries_per_language.len() {
+            self.entries_per_language.resize(index + 1, 0);
+        }
+        &mut self.entries_per_language[index]
+    }
+}
+
+struct Shard {
+    // Unbounded on purpose: eviction is driven by `Shard::evict` so that
+    // entry and byte limits are handled the same way
+    entries: LruCache<CacheKey, String, RandomState>,
+    bytes: usize,
+    counters: ShardCounters,
+}
+
+impl Shard {
+    fn new(hasher: &RandomState) -> Self {
+        Shard {
+            entries: LruCache::unbounded_with_hasher(hasher.clone()),
+            bytes: 0,
+            counters: ShardCounters::default(),
+        }
+    }
+
+    // Rebuild the LRU list keeping only matching entries. The new table is
+    // sized for what is left, and recency order is preserved.
+    fn retain(&mut self, hasher: &RandomState, keep: impl Fn(&CacheKey) -> bool) {
+        let mut kept = LruCache::unbounded_with_hasher(hasher.clone());
+        while let Some((key, stem)) = self.entries.pop_lru() {
+            if keep(&key) {
+                kept.push(key, stem);
+            } else {
+                self.bytes -= entry_size(key.1.len(), stem.len());
+                *self.counters.language_entries(key.0) -= 1;
+            }
+        }
+        self.entries = kept;
+    }
+
+    fn evict(&mut self, max_entries: usize, max_bytes: usize) {
+        while self.entries.len() > max_entries || self.bytes > max_bytes {
+            match self.entries.pop_lru() {
+                Some((key, stem)) => {
+                    self.bytes -= entry_size(key.1.len(), stem.len());
+                    self.counters.evictions += 1;
+                    *self.counters.language_entries(key.0) -= 1;
+                }
+                None => break,
+            }
+        }
+    }
+}
+
+// Sharded LRU cache: each shard is an independent LRU list behind its own
+// mutex, so parallel stemming only contends on words that hash together.
+// Limits are split across the shards in use.
+pub struct StemCache {
+    shards: Box<[Mutex<Shard>]>,
+    // Words hash to the first `active` shards only. Changed with every shard
+    // locked, so it can be trusted while holding any one of them.
+    active: AtomicUsize,
+    hasher: RandomState,
+    max_entries: AtomicUsize,
+    max_bytes: AtomicUsize,
+    // Lookup frequencies for admission, picked by hash like the shards but
+    // independent of the active shard count. Only allocated once a stemmer
+    // with a frequency-based admission policy uses them.
+    sketches: Box<[OnceLock<FrequencySketch>]>,
+    // Identifies this cache's tables in the thread-local L1 caches
+    id: u64,
+    // Bumped whenever entries are removed on request, invalidating L1 tables
+    generation: AtomicU64,
+    // Entries per thread-local L1 table, 0 disables the L1 cache
+    l1_capacity: AtomicUsize,
+    l1_hits: AtomicU64,
+}
+
+impl Default for StemCache {
+    fn default() -> Self {
+        StemCache::new(
+            Some(DEFAULT_MAX_ENTRIES),
+            None,
+            default_shard_count(),
+            DEFAULT_L1_CAPACITY,
+        )
+    }
+}
+
+impl StemCache {
+    pub fn new(
+        max_entries: Option<usize>,
+        max_bytes: Option<usize>,
+        shards: usize,
+        l1_capacity: usize,
+    ) -> Self {
+        let hasher = RandomState::default();
+        let shards = shards.max(1);
+        let (max_entries, max_bytes) = (encode_limit(max_entries), encode_limit(max_bytes));
+        StemCache {
+            shards: (0..shards)
+                .map(|_| Mutex::new(Shard::new(&hasher)))
+                .collect(),
+            active: AtomicUsize::new(active_shards(shards, max_entries, max_bytes)),
+            hasher,
+            max_entries: AtomicUsize::new(max_entries),
+            max_bytes: AtomicUsize::new(max_bytes),
+            sketches: (0..shards).map(|_| OnceLock::new()).collect(),
+            id: NEXT_CACHE_ID.fetch_add(1, Ordering::Relaxed),
+            generation: AtomicU64::new(0),
+            l1_capacity: AtomicUsize::new(l1_capacity),
+            l1_hits: AtomicU64::new(0),
+        }
+    }
+
+    // Start a batch of lookups going through this thread's L1 cache
+    #[inline(always)]
+    pub fn session(&self) -> CacheSession<'_> {
+        CacheSession {
+            cache: self,
+            generation: self.generation.load(Ordering::Acquire),
+            l1_capacity: self.l1_capacity.load(Ordering::Relaxed),
+            l1_hits: 0,
+        }
+    }
+
+    pub fn shard_count(&self) -> usize {
+        self.shards.len()
+    }
+
+    pub fn l1_capacity(&self) -> usize {
+        self.l1_capacity.load(Ordering::Relaxed)
+    }
+
+    pub fn set_l1_capacity(&self, capacity: usize) {
+        self.l1_capacity.store(capacity, Ordering::Relaxed);
+    }
+
+    #[inline(always)]
+    fn lock(shard: &Mutex<Shard>) -> MutexGuard<'_, Shard> {
+        shard.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    // The shard a word hashes to, with its index and the active shard count
+    // it was picked with. Retried if `set_limits` moved entries meanwhile.
+    #[inline(always)]
+    fn shard(&self, hash: u64) -> (usize, usize, MutexGuard<'_, Shard>) {
+        loop {
+            let active = self.active.load(Ordering::Relaxed);
+            let index = hash as usize % active;
+            let shard = Self::lock(&self.shards[index]);
+            if self.active.load(Ordering::Relaxed) == active {
+                return (index, active, shard);
+            }
+        }
+    }
+
+    // The entry and byte limits of one shard
+    #[inline(always)]
+    fn shard_limits(&self, index: usize, active: usize) -> (usize, usize) {
+        (
+            shard_share(self.max_entries.load(Ordering::Relaxed), index, active),
+            shard_share(self.max_bytes.load(Ordering::Relaxed), index, active),
+        )
+    }
+
+    #[inline(always)]
+    fn sketch(&self, hash: u64) -> &FrequencySketch {
+        self.sketches[hash as usize % self.sketches.len()].get_or_init(FrequencySketch::new)
+    }
+
+    // Count a lookup for frequency-based admission
+    #[inline(always)]
+    fn record_sighting(&self, hash: u64, admission: Admission) {
+        if admission.tracks_frequency() {
+            self.sketch(hash).increment(hash);
+        }
+    }
+
+    fn admit(
+        &self,
+        shard: &Shard,
+        admission: Admission,
+        hash: u64,
+        size: usize,
+        max_entries: usize,
+        max_bytes: usize,
+    ) -> bool {
+        match admission {
+            Admission::Always => true,
+            Admission::MinSightings(sightings) => self.sketch(hash).estimate(hash) >= sightings,
+            Admission::TinyLfu => {
+                let has_room = shard.entries.len() < max_entries && shard.bytes + size <= max_bytes;
+                let victim_hash = match shard.entries.peek_lru() {
+                    Some((victim, _)) if !has_room => self.hasher.hash_one(victim.view()),
+                    _ => return true,
+                };
+                self.sketch(hash).estimate(hash) > self.sketch(victim_hash).estimate(victim_hash)
+            }
+        }
+    }
+
+    #[inline(always)]
+    fn get(&self, hash: u64, key: (u8, &str), admission: Admission) -> Option<String> {
+        self.record_sighting(hash, admission);
+        let (_, _, mut shard) = self.shard(hash);
+        let stem = shard.entries.get(&key as &dyn KeyView).cloned();
+        if stem.is_some() {
+            shard.counters.hits += 1;
+        } else {
+            shard.counters.misses += 1;
+        }
+        stem
+    }
+
+    // Make an entry answered by an L1 cache the most recently used of its
+    // shard
+    #[cold]
+    fn promote(&self, hash: u64, key: (u8, &str)) {
+        let (_, _, mut shard) = self.shard(hash);
+        shard.entries.promote(&key as &dyn KeyView);
+    }
+
+    // Store a stem unless the admission policy rejects it, returning whether
+    // it was stored. Callers are expected to have looked the word up with the
+    // same policy first, which is what records its frequency.
+    pub fn insert(&self, lang: u8, word: &str, stem: String, admission: Admission) -> bool {
+        let hash = self.hasher.hash_one((lang, word));
+        self.insert_hashed(hash, lang, word, stem, admission)
+    }
+
+    fn insert_hashed(
+        &self,
+        hash: u64,
+        lang: u8,
+        word: &str,
+        stem: String,
+        admission: Admission,
+    ) -> bool {
+        let word_len = word.len();
+        let size = entry_size(word_len, stem.len());
+        let (index, active, mut shard) = self.shard(hash);
+        let (max_entries, max_bytes) = self.shard_limits(index, active);
+        // A limit of 0, or an entry bigger than the shard's byte budget:
+        // storing it would only evict it again
+        if max_entries == 0 || size > max_bytes {
+            return false;
+        }
+        if !self.admit(&shard, admission, hash, size, max_entries, max_bytes) {
+            shard.counters.rejections += 1;
+            return false;
+        }
+        match shard.entries.put((lang, word.to_owned()), stem) {
+            Some(old) => shard.bytes -= entry_size(word_len, old.len()),
+            None => *shard.counters.language_entries(lang) += 1,
+        }
+        shard.bytes += size;
+        shard.counters.inserts += 1;
+        shard.evict(max_entries, max_bytes);
+        true
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        let l1_hits = self.l1_hits.load(Ordering::Relaxed);
+        let mut stats = CacheStats {
+            hits: l1_hits,
+            l1_hits,
+            ..CacheStats::default()
+        };
+        for shard in self.shards.iter() {
+            let shard = Self::lock(shard);
+            stats.hits += shard.counters.hits;
+            stats.misses += shard.counters.misses;
+            stats.inserts += shard.counters.inserts;
+            stats.evictions += shard.counters.evictions;
+            stats.rejections += shard.counters.rejections;
+            stats.entries += shard.entries.len();
+            stats.memory_bytes += shard.bytes;
+            for (lang, &count) in shard.counters.entries_per_language.iter().enumerate() {
+                if count > 0 {
+                    *stats.entries_per_language.entry(lang as u8).or_default() += count;
+                }
+            }
+        }
+        stats
+    }
+
+    // Visit every entry, least recently used first within each shard
+    pub fn for_each_entry(&self, mut f: impl FnMut(&CacheKey, &str)) {
+        for shard in self.shards.iter() {
+            for (key, stem) in Self::lock(shard).entries.iter().rev() {
+                f(key, stem);
+            }
+        }
+    }
+
+    pub fn limits(&self) -> (Option<usize>, Option<usize>) {
+        (
+            decode_limit(self.max_entries.load(Ordering::Relaxed)),
+            decode_limit(self.max_bytes.load(Ordering::Relaxed)),
+        )
+    }
+
+    // Update the limits and evict right away if the cache is now over them.
+    // When the number of shards in use changes, entries move to the shard
+    // they now hash to.
+    pub fn set_limits(&self, max_entries: Option<usize>, max_bytes: Option<usize>) {
+        let mut shards: Vec<MutexGuard<'_, Shard>> = self.shards.iter().map(Self::lock).collect();
+        let (max_entries, max_bytes) = (encode_limit(max_entries), encode_limit(max_bytes));
+        self.max_entries.store(max_entries, Ordering::Relaxed);
+        self.max_bytes.store(max_bytes, Ordering::Relaxed);
+        let active = active_shards(shards.len(), max_entries, max_bytes);
+        if active != self.active.swap(active, Ordering::Relaxed) {
+            self.reshard(&mut shards, active);
+        }
+        for (index, shard) in shards.iter_mut().enumerate().take(active) {
+            let (max_entries, max_bytes) = self.shard_limits(index, active);
+            shard.evict(max_entries, max_bytes);
+        }
+    }
+
+    // Take the entries out of every shard, least recently used first and
+    // shard by shard in turn, and put them where they hash to now
+    fn reshard(&self, shards: &mut [MutexGuard<'_, Shard>], active: usize) {
+        let mut moved = Vec::new();
+        loop {
+            let before = moved.len();
+            for shard in shards.iter_mut() {
+                if let Some((key, stem)) = shard.entries.pop_lru() {
+                    shard.bytes -= entry_size(key.1.len(), stem.len());
+                    *shard.counters.language_entries(key.0) -= 1;
+                    moved.push((key, stem));
+                }
+            }
+            if moved.len() == before {
+                break;
+            }
+        }
+        for (key, stem) in moved {
+            let hash = self.hasher.hash_one(key.view());
+            let shard = &mut shards[hash as usize % active];
+            shard.bytes += entry_size(key.1.len(), stem.len());
+            *shard.counters.language_entries(key.0) += 1;
+            shard.entries.push(key, stem);
+        }
+    }
+
+    // Drop every entry and the memory backing it, and forget lookup
+    // frequencies; lookup counters are kept
+    pub fn clear(&self) {
+        self.generation.fetch_add(1, Ordering::Release);
+        for shard in self.shards.iter() {
+            let mut shard = Self::lock(shard);
+            shard.entries = LruCache::unbounded_with_hasher(self.hasher.clone());
+            shard.bytes = 0;
+            shard.counters.entries_per_language.clear();
+        }
+        for sketch in self.sketches.iter().filter_map(OnceLock::get) {
+            sketch.reset();
+        }
+    }
+
+    pub fn clear_language(&self, lang: u8) {
+        self.generation.fetch_add(1, Ordering::Release);
+        for shard in self.shards.iter() {
+            let mut shard = Self::lock(shard);
+            if shard
+                .counters
+                .entries_per_language
+                .get(lang as usize)
+                .is_some_and(|&count| count > 0)
+            {
+                shard.retain(&self.hasher, |key| key.0 != lang);
+            }
+        }
+    }
+
+    pub fn shrink_to_fit(&self) {
+        for shard in self.shards.iter() {
+            Self::lock(shard).retain(&self.hasher, |_| true);
+        }
+    }
+}
+
+// Lookups and inserts for one call (or one rayon job). L1 hits are counted
+// locally and published when the session is dropped, so hot-path hits never
+// touch a shared counter.
+pub struct CacheSession<'a> {
+    cache: &'a StemCache,
+    generation: u64,
+    l1_capacity: usize,
+    l1_hits: u64,
+}
+
+impl CacheSession<'_> {
+    #[inline(always)]
+    pub fn get(&mut self, lang: u8, word: &str, admission: Admission) -> Option<String> {
+        let cache = self.cache;
+        let key = (lang, word);
+        let hash = cache.hasher.hash_one(key);
+        if self.l1_capacity == 0 {
+            return cache.get(hash, key, admission);
+        }
+
+        if let Some((stem, due)) = l1::get(cache.id, self.generation, self.l1_capacity, hash, key) {
+            self.l1_hits += 1;
+            cache.record_sighting(hash, admission);
+            if due {
+                cache.promote(hash, key);
+            }
+            return Some(stem);
+        }
+        let stem = cache.get(hash, key, admission)?;
+        l1::put(
+            cache.id,
+            self.generation,
+            self.l1_capacity,
+            hash,
+            key,
+            &stem,
+        );
+        Some(stem)
+    }
+
+    #[inline(always)]
+    pub fn insert(&mut self, lang: u8, word: &str, stem: &str, admission: Admission) {
+        let cache = self.cache;
+        let key = (lang, word);
+        let hash = cache.hasher.hash_one(key);
+        let stored = cache.insert_hashed(hash, lang, word, stem.to_owned(), admission);
+        if stored && self.l1_capacity > 0 {
+            l1::put(cache.id, self.generation, self.l1_capacity, hash, key, stem);
+        }
+    }
+}
+
+// Stem `word` through the cache under `lang` when there is a session, and
+// directly otherwise. Shared by every stemmer class.
+#[inline(always)]
+pub fn stem_through(
+    session: Option<&mut CacheSession<'_>>,
+    lang: u8,
+    stemmer: &Stemmer,
+    word: &str,
+    admission: Admission,
+) -> String {
+    let Some(session) = session else {
+        return stemmer.stem(word).into_owned();
+    };
+    if let Some(stem) = session.get(lang, word, admission) {
+        return stem;
+    }
+    let result = stemmer.stem(word).into_owned();
+    session.insert(lang, word, &result, admission);
+    result
+}
+
+impl Drop for CacheSession<'_> {
+    fn drop(&mut self) {
+        if self.l1_hits > 0 {
+            self.cache
+                .l1_hits
+                .fetch_add(self.l1_hits, Ordering::Relaxed);
+        }
+    }
+}
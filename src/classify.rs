@@ -0,0 +1,414 @@
+// Tokens that are not words: numbers, URLs, emails, @mentions, #hashtags and
+// emoji. Snowball strips suffixes from these too ("3.5GHz", "https://..."),
+// producing garbage that also takes up cache entries, so a `TokenPolicy` can
+// keep them as they are, drop them, or normalize them instead of stemming.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyTuple;
+use std::borrow::Cow;
+use std::hash::{Hash, Hasher};
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum TokenClass {
+    Url,
+    Email,
+    Mention,
+    Hashtag,
+    Emoji,
+    // Starts with a digit, after an optional sign or currency symbol:
+    // "2024", "-3.5", "$20", "3.5GHz", "19th"
+    Number,
+}
+
+impl TokenClass {
+    fn matches(self, word: &str) -> bool {
+        match self {
+            TokenClass::Url => is_url(word),
+            TokenClass::Email => is_email(word),
+            TokenClass::Mention => word
+                .strip_prefix('@')
+                .is_some_and(|name| !name.is_empty() && name.chars().all(is_name_char)),
+            TokenClass::Hashtag => word.strip_prefix('#').is_some_and(|tag| {
+                tag.chars().all(is_name_char) && tag.chars().any(char::is_alphabetic)
+            }),
+            TokenClass::Emoji => {
+                word.chars().any(is_pictographic)
+                    && word
+                        .chars()
+                        .all(|c| is_pictographic(c) || is_emoji_component(c))
+            }
+            TokenClass::Number => word
+                .trim_start_matches(['+', '-', '$', '€', '£', '¥'])
+                .starts_with(|c: char| c.is_numeric()),
+        }
+    }
+
+    // What `normalize` does with a token of this class
+    fn normalize(self, word: &str) -> Action<'_> {
+        match self {
+            // The tag is a word like any other, and tags differing only in
+            // case are the same tag
+            TokenClass::Hashtag => Action::Stem(lowercase(&word[1..])),
+            TokenClass::Url => Action::Verbatim(normalize_url(word)),
+            TokenClass::Email | TokenClass::Mention => Action::Verbatim(lowercase(word)),
+            TokenClass::Emoji => Action::Verbatim(normalize_emoji(word)),
+            TokenClass::Number => Action::Verbatim(normalize_number(word)),
+        }
+    }
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn is_url(word: &str) -> bool {
+    ["http://", "https://", "ftp://", "www."]
+        .iter()
+        .any(|scheme| {
+            word.len() > scheme.len()
+                && word.is_char_boundary(scheme.len())
+                && word[..scheme.len()].eq_ignore_ascii_case(scheme)
+        })
+}
+
+fn is_email(word: &str) -> bool {
+    let Some((local, domain)) = word.split_once('@') else {
+        return false;
+    };
+    !local.is_empty()
+        && local
+            .chars()
+            .all(|c| c.is_alphanumeric() || "._%+-'".contains(c))
+        && domain.contains('.')
+        && !domain.starts_with('.')
+        && !domain.ends_with('.')
+        && domain
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '.' || c == '-')
+}
+
+// Pictographs in the ranges emoji are drawn from; a close approximation of
+// Unicode's Extended_Pictographic property
+fn is_pictographic(c: char) -> bool {
+    matches!(c,
+        '\u{1F000}'..='\u{1FAFF}'
+        | '\u{2190}'..='\u{21FF}'
+        | '\u{2300}'..='\u{23FF}'
+        | '\u{25A0}'..='\u{27BF}'
+        | '\u{2900}'..='\u{297F}'
+        | '\u{2B00}'..='\u{2BFF}'
+        | '\u{A9}' | '\u{AE}' | '\u{203C}' | '\u{2049}' | '\u{2122}' | '\u{2139}'
+        | '\u{24C2}' | '\u{3030}' | '\u{303D}' | '\u{3297}' | '\u{3299}')
+}
+
+// Joiners, presentation selectors, skin tones, keycaps and flag tags
+fn is_emoji_component(c: char) -> bool {
+    matches!(c,
+        '\u{200D}' | '\u{FE0E}' | '\u{FE0F}' | '\u{20E3}'
+        | '\u{1F3FB}'..='\u{1F3FF}'
+        | '\u{E0020}'..='\u{E007F}')
+}
+
+fn lowercase(word: &str) -> Cow<'_, str> {
+    if word.chars().any(char::is_uppercase) {
+        Cow::Owned(word.to_lowercase())
+    } else {
+        Cow::Borrowed(word)
+    }
+}
+
+// Lowercase scheme and host, no trailing slash; the path is case-sensitive
+fn normalize_url(url: &str) -> Cow<'_, str> {
+    let url = url.strip_suffix('/').unwrap_or(url);
+    let host_start = url.find("://").map_or(0, |i| i + 3);
+    let host_end = url[host_start..]
+        .find(['/', '?', '#'])
+        .map_or(url.len(), |i| host_start + i);
+    match lowercase(&url[..host_end]) {
+        Cow::Borrowed(_) => Cow::Borrowed(url),
+        Cow::Owned(prefix) => Cow::Owned(prefix + &url[host_end..]),
+    }
+}
+
+// Skin tones and presentation selectors dropped, so variants of one emoji
+// become one token
+fn normalize_emoji(emoji: &str) -> Cow<'_, str> {
+    let variant = |c: char| matches!(c, '\u{FE0E}' | '\u{FE0F}' | '\u{1F3FB}'..='\u{1F3FF}');
+    if emoji.contains(variant) {
+        Cow::Owned(emoji.chars().filter(|&c| !variant(c)).collect())
+    } else {
+        Cow::Borrowed(emoji)
+    }
+}
+
+// Units lowercased ("3.5GHz" -> "3.5ghz") and thousands separators dropped
+// ("1,000,000" -> "1000000"). A comma not followed by exactly three digits
+// is a decimal comma and stays.
+fn normalize_number(number: &str) -> Cow<'_, str> {
+    let number = lowercase(number);
+    let bytes = number.as_bytes();
+    let separator = |i: usize| {
+        matches!(bytes[i], b',' | b'_')
+            && i > 0
+            && bytes[i - 1].is_ascii_digit()
+            && bytes.len() >= i + 4
+            && bytes[i + 1..i + 4].iter().all(u8::is_ascii_digit)
+            && !bytes.get(i + 4).is_some_and(u8::is_ascii_digit)
+    };
+    if !(0..bytes.len()).any(separator) {
+        return number;
+    }
+    let kept: Vec<u8> = (0..bytes.len())
+        .filter(|&i| !separator(i))
+        .map(|i| bytes[i])
+        .collect();
+    // Only ASCII separators were removed
+    Cow::Owned(String::from_utf8(kept).expect("removing ASCII keeps UTF-8 valid"))
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Policy {
+    Keep,
+    Drop,
+    Normalize,
+}
+
+impl Policy {
+    fn parse(policy: Option<&str>) -> PyResult<Option<Self>> {
+        match policy.map(str::to_lowercase).as_deref() {
+            None => Ok(None),
+            Some("keep") => Ok(Some(Policy::Keep)),
+            Some("drop") => Ok(Some(Policy::Drop)),
+            Some("normalize") => Ok(Some(Policy::Normalize)),
+            Some(_) => Err(PyValueError::new_err(format!(
+                "Unsupported token policy: {} (expected 'keep', 'drop' or 'normalize')",
+                policy.unwrap_or_default()
+            ))),
+        }
+    }
+
+    fn name(policy: Option<Self>) -> Option<&'static str> {
+        policy.map(|policy| match policy {
+            Policy::Keep => "keep",
+            Policy::Drop => "drop",
+            Policy::Normalize => "normalize",
+        })
+    }
+}
+
+// What the stemmer does with one token
+pub enum Action<'a> {
+    Drop,
+    // Returned as is, without stemming or caching
+    Verbatim(Cow<'a, str>),
+    Stem(Cow<'a, str>),
+}
+
+// The policy for each class of token; classes without one are stemmed like
+// words, as they are without a `TokenPolicy`
+#[pyclass(module = "py_rust_stemmers_tuned", frozen)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TokenPolicy {
+    numbers: Option<Policy>,
+    urls: Option<Policy>,
+    emails: Option<Policy>,
+    mentions: Option<Policy>,
+    hashtags: Option<Policy>,
+    emoji: Option<Policy>,
+}
+
+// Classes in the order `classify` tries them. URLs and emails come before
+// numbers, which they can start like.
+const CLASSES: [TokenClass; 6] = [
+    TokenClass::Url,
+    TokenClass::Email,
+    TokenClass::Mention,
+    TokenClass::Hashtag,
+    TokenClass::Emoji,
+    TokenClass::Number,
+];
+
+// The classes whose tokens span several UAX #29 word segments
+const CHUNK_CLASSES: [TokenClass; 4] = [
+    TokenClass::Url,
+    TokenClass::Email,
+    TokenClass::Mention,
+    TokenClass::Hashtag,
+];
+
+impl TokenPolicy {
+    pub const NONE: TokenPolicy = TokenPolicy {
+        numbers: None,
+        urls: None,
+        emails: None,
+        mentions: None,
+        hashtags: None,
+        emoji: None,
+    };
+
+    fn policy(&self, class: TokenClass) -> Option<Policy> {
+        match class {
+            TokenClass::Url => self.urls,
+            TokenClass::Email => self.emails,
+            TokenClass::Mention => self.mentions,
+            TokenClass::Hashtag => self.hashtags,
+            TokenClass::Emoji => self.emoji,
+            TokenClass::Number => self.numbers,
+        }
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        *self == TokenPolicy::NONE
+    }
+
+    pub fn has_chunk_classes(&self) -> bool {
+        CHUNK_CLASSES
+            .iter()
+            .any(|&class| self.policy(class).is_some())
+    }
+
+    // The first class with a policy that `word` belongs to
+    #[inline(always)]
+    pub fn classify(&self, word: &str) -> Option<TokenClass> {
+        if self.is_empty() {
+            return None;
+        }
+        self.classify_among(&CLASSES, word)
+    }
+
+    // Like `classify`, for a whole whitespace-separated chunk of text
+    pub fn classify_chunk(&self, chunk: &str) -> Option<TokenClass> {
+        self.classify_among(&CHUNK_CLASSES, chunk)
+    }
+
+    fn classify_among(&self, classes: &[TokenClass], word: &str) -> Option<TokenClass> {
+        classes
+            .iter()
+            .copied()
+            .find(|&class| self.policy(class).is_some() && class.matches(word))
+    }
+
+    // Whether `word` is a token that this policy drops
+    pub fn drops(&self, word: &str) -> bool {
+        self.classify(word)
+            .is_some_and(|class| self.policy(class) == Some(Policy::Drop))
+    }
+
+    // What to do with `word`, a token of `class`
+    pub fn action<'a>(&self, class: TokenClass, word: &'a str) -> Action<'a> {
+        match self.policy(class) {
+            Some(Policy::Drop) => Action::Drop,
+            Some(Policy::Keep) => Action::Verbatim(Cow::Borrowed(word)),
+            Some(Policy::Normalize) => class.normalize(word),
+            None => Action::Stem(Cow::Borrowed(word)),
+        }
+    }
+}
+
+#[pymethods]
+impl TokenPolicy {
+    // Each argument is "keep", "drop" or "normalize"
+    #[new]
+    #[pyo3(signature = (
+        numbers = None,
+        urls = None,
+        emails = None,
+        mentions = None,
+        hashtags = None,
+        emoji = None,
+    ))]
+    fn new(
+        numbers: Option<&str>,
+        urls: Option<&str>,
+        emails: Option<&str>,
+        mentions: Option<&str>,
+        hashtags: Option<&str>,
+        emoji: Option<&str>,
+    ) -> PyResult<Self> {
+        Ok(TokenPolicy {
+            numbers: Policy::parse(numbers)?,
+            urls: Policy::parse(urls)?,
+            emails: Policy::parse(emails)?,
+            mentions: Policy::parse(mentions)?,
+            hashtags: Policy::parse(hashtags)?,
+            emoji: Policy::parse(emoji)?,
+        })
+    }
+
+    #[getter]
+    fn numbers(&self) -> Option<&'static str> {
+        Policy::name(self.numbers)
+    }
+
+    #[getter]
+    fn urls(&self) -> Option<&'static str> {
+        Policy::name(self.urls)
+    }
+
+    #[getter]
+    fn emails(&self) -> Option<&'static str> {
+        Policy::name(self.emails)
+    }
+
+    #[getter]
+    fn mentions(&self) -> Option<&'static str> {
+        Policy::name(self.mentions)
+    }
+
+    #[getter]
+    fn hashtags(&self) -> Option<&'static str> {
+        Policy::name(self.hashtags)
+    }
+
+    #[getter]
+    fn emoji(&self) -> Option<&'static str> {
+        Policy::name(self.emoji)
+    }
+
+    fn __eq__(&self, other: &Self) -> bool {
+        self == other
+    }
+
+    fn __hash__(&self) -> u64 {
+        let mut hasher = std::hash::DefaultHasher::new();
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn __reduce__<'py>(
+        &self,
+        py: Python<'py>,
+    ) -> PyResult<(Bound<'py, PyAny>, Bound<'py, PyTuple>)> {
+        let args = (
+            self.numbers(),
+            self.urls(),
+            self.emails(),
+            self.mentions(),
+            self.hashtags(),
+            self.emoji(),
+        );
+        Ok((
+            py.get_type::<TokenPolicy>().into_any(),
+            args.into_pyobject(py)?,
+        ))
+    }
+
+    // Classes without a policy are omitted
+    fn __repr__(&self) -> String {
+        let fields = [
+            ("numbers", self.numbers()),
+            ("urls", self.urls()),
+            ("emails", self.emails()),
+            ("mentions", self.mentions()),
+            ("hashtags", self.hashtags()),
+            ("emoji", self.emoji()),
+        ];
+        let set: Vec<String> = fields
+            .iter()
+            .filter_map(|(name, policy)| policy.map(|policy| format!("{}={:?}", name, policy)))
+            .collect();
+        format!("TokenPolicy({})", set.join(", "))
+    }
+}
@@ -0,0 +1,209 @@
+// Automatic language detection, so mixed-language streams can pick a stemmer
+// per document. Uses whatlang's offline trigram model, restricted to the
+// languages we can stem.
+
+use crate::admission::{Admission, AdmissionArg};
+use crate::algorithms::Algorithm;
+use crate::languages::{self, LANGUAGES};
+use crate::namespace::{CacheArg, PyStemCache};
+use crate::{parse_language, SnowballStemmer};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::sync::OnceLock;
+use whatlang::{Detector, Lang};
+
+pub const DEFAULT_LANGUAGE: &str = "english";
+// whatlang only gives 0.2-0.5 to single sentences in closely related
+// Latin-script languages, even when it picks the right one
+pub const DEFAULT_MIN_CONFIDENCE: f64 = 0.15;
+
+static DETECTOR: OnceLock<Detector> = OnceLock::new();
+
+// A detector that only ever answers with a language we have an algorithm for
+fn detector() -> &'static Detector {
+    DETECTOR.get_or_init(|| {
+        let allowlist = LANGUAGES
+            .iter()
+            .filter_map(|language| {
+                std::iter::once(language.iso639_3)
+                    .chain(language.aliases.iter().copied())
+                    .find_map(Lang::from_code)
+            })
+            .collect();
+        Detector::with_allowlist(allowlist)
+    })
+}
+
+#[pyclass(module = "py_rust_stemmers_tuned", frozen)]
+pub struct LanguageDetection {
+    // The algorithm to stem with
+    algorithm: Algorithm,
+    // Detector confidence in [0, 1]; 0.0 if nothing could be detected
+    #[pyo3(get)]
+    confidence: f64,
+    // True when `algorithm` is the default because detection was not
+    // confident enough
+    #[pyo3(get)]
+    is_fallback: bool,
+}
+
+#[pymethods]
+impl LanguageDetection {
+    #[getter]
+    fn language(&self) -> &'static str {
+        languages::language_of(self.algorithm).name
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "LanguageDetection(language='{}', confidence={:.3}, is_fallback={})",
+            self.language(),
+            self.confidence,
+            if self.is_fallback { "True" } else { "False" }
+        )
+    }
+}
+
+// A document, as one string or as its tokens
+#[derive(FromPyObject)]
+pub enum TextArg {
+    Text(String),
+    Tokens(Vec<String>),
+}
+
+impl TextArg {
+    fn into_text(self) -> String {
+        match self {
+            TextArg::Text(text) => text,
+            TextArg::Tokens(tokens) => tokens.join(" "),
+        }
+    }
+}
+
+fn check_min_confidence(min_confidence: f64) -> PyResult<f64> {
+    if (0.0..=1.0).contains(&min_confidence) {
+        Ok(min_confidence)
+    } else {
+        Err(PyValueError::new_err(format!(
+            "min_confidence must be between 0 and 1, got {}",
+            min_confidence
+        )))
+    }
+}
+
+pub fn detect(text: &str, default: Algorithm, min_confidence: f64) -> LanguageDetection {
+    let info = detector().detect(text);
+    let confidence = info.as_ref().map_or(0.0, |info| info.confidence());
+    let detected = info
+        .filter(|info| info.confidence() >= min_confidence)
+        .and_then(|info| languages::resolve(info.lang().code()).ok());
+    LanguageDetection {
+        algorithm: detected.map_or(default, |language| language.algorithm),
+        confidence,
+        is_fallback: detected.is_none(),
+    }
+}
+
+// Detect the language of a text (or token list), falling back to `default`
+// when the detector is less than `min_confidence` sure
+#[pyfunction]
+#[pyo3(signature = (text, default = DEFAULT_LANGUAGE, min_confidence = DEFAULT_MIN_CONFIDENCE))]
+pub fn detect_language(
+    py: Python<'_>,
+    text: TextArg,
+    default: &str,
+    min_confidence: f64,
+) -> PyResult<LanguageDetection> {
+    let default = parse_language(default)?;
+    let min_confidence = check_min_confidence(min_confidence)?;
+    let text = text.into_text();
+    Ok(py.detach(|| detect(&text, default, min_confidence)))
+}
+
+// Picks the stemmer for each document by detecting its language. All the
+// stemmers it hands out share one cache and admission policy.
+#[pyclass(module = "py_rust_stemmers_tuned", frozen)]
+pub struct AutoStemmer {
+    default: Algorithm,
+    min_confidence: f64,
+    cache: Option<Py<PyStemCache>>,
+    admission: Admission,
+}
+
+impl AutoStemmer {
+    fn detect_document(&self, py: Python<'_>, text: TextArg) -> LanguageDetection {
+        let text = text.into_text();
+        py.detach(|| detect(&text, self.default, self.min_confidence))
+    }
+
+    fn stemmer(&self, py: Python<'_>, detection: &LanguageDetection) -> SnowballStemmer {
+        SnowballStemmer::with_algorithm(
+            detection.algorithm,
+            self.cache.as_ref().map(|cache| cache.clone_ref(py)),
+            self.admission,
+        )
+    }
+}
+
+#[pymethods]
+impl AutoStemmer {
+    #[new]
+    #[pyo3(signature = (
+        default = DEFAULT_LANGUAGE,
+        min_confidence = DEFAULT_MIN_CONFIDENCE,
+        cache = CacheArg::Enabled(true),
+        admission = AdmissionArg::Sightings(1),
+    ))]
+    fn new(
+        py: Python<'_>,
+        default: &str,
+        min_confidence: f64,
+        cache: CacheArg,
+        admission: AdmissionArg,
+    ) -> PyResult<Self> {
+        Ok(AutoStemmer {
+            default: parse_language(default)?,
+            min_confidence: check_min_confidence(min_confidence)?,
+            cache: cache.resolve(py)?,
+            admission: admission.resolve()?,
+        })
+    }
+
+    #[getter]
+    fn default(&self) -> &'static str {
+        languages::language_of(self.default).name
+    }
+
+    #[getter]
+    fn min_confidence(&self) -> f64 {
+        self.min_confidence
+    }
+
+    fn detect(&self, py: Python<'_>, text: TextArg) -> LanguageDetection {
+        self.detect_document(py, text)
+    }
+
+    // The stemmer for the detected language of `text`
+    fn stemmer_for(&self, py: Python<'_>, text: TextArg) -> SnowballStemmer {
+        let detection = self.detect_document(py, text);
+        self.stemmer(py, &detection)
+    }
+
+    // Detect the language of the tokens, then stem them with it
+    fn stem_words(&self, py: Python<'_>, words: Vec<String>) -> (Vec<String>, LanguageDetection) {
+        let detection = py.detach(|| detect(&words.join(" "), self.default, self.min_confidence));
+        (self.stemmer(py, &detection).stem_words(words), detection)
+    }
+
+    fn stem_words_parallel(
+        &self,
+        py: Python<'_>,
+        words: Vec<String>,
+    ) -> PyResult<(Vec<String>, LanguageDetection)> {
+        let detection = py.detach(|| detect(&words.join(" "), self.default, self.min_confidence));
+        let stems = self
+            .stemmer(py, &detection)
+            .stem_words_parallel(py, words)?;
+        Ok((stems, detection))
+    }
+}
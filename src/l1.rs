@@ -0,0 +1,136 @@
+// Thread-local L1 cache in front of the shared, sharded cache. Hot words are
+// answered without taking any lock, which keeps rayon workers from piling up
+// on the shard that holds words like "the".
+//
+// Each thread keeps one small direct-mapped table per cache it has used
+// recently. Stems only depend on (algorithm, word), so an L1 entry can never
+// be wrong; clearing the shared cache bumps its generation, which makes every
+// thread drop its table for that cache on next use.
+//
+// Every `PROMOTE_EVERY` hits answered here, an entry is also made the most
+// recently used of its shard, so hot words are not evicted from the shared
+// cache although most of their lookups never reach it.
+
+use std::cell::RefCell;
+
+// Tables for caches not used recently are dropped beyond this many per thread
+const MAX_TABLES_PER_THREAD: usize = 8;
+
+pub const PROMOTE_EVERY: u32 = 32;
+
+struct Entry {
+    hash: u64,
+    lang: u8,
+    word: Box<str>,
+    stem: Box<str>,
+    // Hits since the entry was last promoted in its shard
+    hits: u32,
+}
+
+struct Table {
+    cache_id: u64,
+    generation: u64,
+    last_used: u64,
+    slots: Box<[Option<Entry>]>,
+}
+
+impl Table {
+    fn new(cache_id: u64, generation: u64, capacity: usize) -> Self {
+        Table {
+            cache_id,
+            generation,
+            last_used: 0,
+            slots: (0..capacity.next_power_of_two()).map(|_| None).collect(),
+        }
+    }
+
+    // Shards are picked from the low bits of the hash, so use the high ones
+    #[inline(always)]
+    fn slot(&self, hash: u64) -> usize {
+        (hash >> 32) as usize & (self.slots.len() - 1)
+    }
+}
+
+#[derive(Default)]
+struct Tables {
+    tables: Vec<Table>,
+    clock: u64,
+}
+
+impl Tables {
+    // The table for `cache_id`, reset if the cache was cleared or resized
+    fn table(&mut self, cache_id: u64, generation: u64, capacity: usize) -> &mut Table {
+        self.clock += 1;
+        let index = match self.tables.iter().position(|t| t.cache_id == cache_id) {
+            Some(index) => index,
+            None => {
+                if self.tables.len() >= MAX_TABLES_PER_THREAD {
+                    let oldest = (0..self.tables.len())
+                        .min_by_key(|&i| self.tables[i].last_used)
+                        .unwrap_or(0);
+                    self.tables.swap_remove(oldest);
+                }
+                self.tables.push(Table::new(cache_id, generation, capacity));
+                self.tables.len() - 1
+            }
+        };
+        let table = &mut self.tables[index];
+        if table.generation != generation || table.slots.len() != capacity.next_power_of_two() {
+            *table = Table::new(cache_id, generation, capacity);
+        }
+        table.last_used = self.clock;
+        table
+    }
+}
+
+thread_local! {
+    static TABLES: RefCell<Tables> = RefCell::default();
+}
+
+// The stem, and whether the entry is due to be promoted in its shard
+#[inline(always)]
+pub fn get(
+    cache_id: u64,
+    generation: u64,
+    capacity: usize,
+    hash: u64,
+    (lang, word): (u8, &str),
+) -> Option<(String, bool)> {
+    TABLES.with_borrow_mut(|tables| {
+        let table = tables.table(cache_id, generation, capacity);
+        let slot = table.slot(hash);
+        match &mut table.slots[slot] {
+            Some(entry) if entry.hash == hash && entry.lang == lang && &*entry.word == word => {
+                entry.hits += 1;
+                let due = entry.hits == PROMOTE_EVERY;
+                if due {
+                    entry.hits = 0;
+                }
+                Some((entry.stem.to_string(), due))
+            }
+            _ => None,
+        }
+    })
+}
+
+#[inline(always)]
+pub fn put(
+    cache_id: u64,
+    generation: u64,
+    capacity: usize,
+    hash: u64,
+    (lang, word): (u8, &str),
+    stem: &str,
+) {
+    TABLES.with_borrow_mut(|tables| {
+        let table = tables.table(cache_id, generation, capacity);
+        let slot = table.slot(hash);
+        table.slots[slot] = Some(Entry {
+            hash,
+            lang,
+            word: word.into(),
+            stem: stem.into(),
+            hits: 0,
+        });
+    })
+}
@@ -0,0 +1,306 @@
+// Supported languages and the names they can be requested by: canonical
+// English names, ISO 639-1 and 639-3 codes, and common aliases (ISO 639-2/B
+// codes, endonyms, algorithm names). Locale tags such as "pt-BR" or "en_US"
+// resolve through their primary language subtag.
+
+use crate::algorithms::Algorithm;
+use crate::stopwords;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+pub struct Language {
+    pub name: &'static str,
+    pub algorithm: Algorithm,
+    pub iso639_1: &'static str,
+    pub iso639_3: &'static str,
+    pub aliases: &'static [&'static str],
+    // ISO 15924 name of the script the algorithm expects
+    pub script: &'static str,
+    // Whether the algorithm has a built-in list of irregular forms
+    pub has_exceptions: bool,
+}
+
+pub const LANGUAGES: [Language; 20] = [
+    Language {
+        name: "arabic",
+        algorithm: Algorithm::Arabic,
+        iso639_1: "ar",
+        iso639_3: "ara",
+        aliases: &["arb", "العربية"],
+        script: "Arabic",
+        has_exceptions: false,
+    },
+    Language {
+        name: "danish",
+        algorithm: Algorithm::Danish,
+        iso639_1: "da",
+        iso639_3: "dan",
+        aliases: &["dansk"],
+        script: "Latin",
+        has_exceptions: false,
+    },
+    Language {
+        name: "dutch",
+        algorithm: Algorithm::Dutch,
+        iso639_1: "nl",
+        iso639_3: "nld",
+        aliases: &["dut", "nederlands", "flemish", "vlaams"],
+        script: "Latin",
+        has_exceptions: false,
+    },
+    Language {
+        name: "english",
+        algorithm: Algorithm::English,
+        iso639_1: "en",
+        iso639_3: "eng",
+        aliases: &["porter2"],
+        script: "Latin",
+        has_exceptions: true,
+    },
+    Language {
+        name: "finnish",
+        algorithm: Algorithm::Finnish,
+        iso639_1: "fi",
+        iso639_3: "fin",
+        aliases: &["suomi"],
+        script: "Latin",
+        has_exceptions: false,
+    },
+    Language {
+        name: "french",
+        algorithm: Algorithm::French,
+        iso639_1: "fr",
+        iso639_3: "fra",
+        aliases: &["fre", "français", "francais"],
+        script: "Latin",
+        has_exceptions: false,
+    },
+    Language {
+        name: "german",
+        algorithm: Algorithm::German,
+        iso639_1: "de",
+        iso639_3: "deu",
+        aliases: &["ger", "deutsch"],
+        script: "Latin",
+        has_exceptions: false,
+    },
+    Language {
+        name: "greek",
+        algorithm: Algorithm::Greek,
+        iso639_1: "el",
+        iso639_3: "ell",
+        aliases: &["gre", "modern greek", "ελληνικά"],
+        script: "Greek",
+        has_exceptions: true,
+    },
+    Language {
+        name: "hindi",
+        algorithm: Algorithm::Hindi,
+        iso639_1: "hi",
+        iso639_3: "hin",
+        aliases: &["हिन्दी", "हिंदी"],
+        script: "Devanagari",
+        has_exceptions: false,
+    },
+    Language {
+        name: "hungarian",
+        algorithm: Algorithm::Hungarian,
+        iso639_1: "hu",
+        iso639_3: "hun",
+        aliases: &["magyar"],
+        script: "Latin",
+        has_exceptions: false,
+    },
+    Language {
+        name: "indonesian",
+        algorithm: Algorithm::Indonesian,
+        iso639_1: "id",
+        iso639_3: "ind",
+        // "in" is the code ISO 639-1 used before 1989
+        aliases: &["in", "bahasa indonesia"],
+        script: "Latin",
+        has_exceptions: false,
+    },
+    Language {
+        name: "italian",
+        algorithm: Algorithm::Italian,
+        iso639_1: "it",
+        iso639_3: "ita",
+        aliases: &["italiano"],
+        script: "Latin",
+        has_exceptions: false,
+    },
+    Language {
+        name: "norwegian",
+        algorithm: Algorithm::Norwegian,
+        iso639_1: "no",
+        iso639_3: "nor",
+        // The Snowball algorithm targets Bokmål
+        aliases: &[
+            "nb",
+            "nob",
+            "norsk",
+            "bokmål",
+            "bokmal",
+            "norwegian bokmål",
+            "norwegian bokmal",
+        ],
+        script: "Latin",
+        has_exceptions: false,
+    },
+    Language {
+        name: "portuguese",
+        algorithm: Algorithm::Portuguese,
+        iso639_1: "pt",
+        iso639_3: "por",
+        aliases: &["português", "portugues"],
+        script: "Latin",
+        has_exceptions: false,
+    },
+    Language {
+        name: "romanian",
+        algorithm: Algorithm::Romanian,
+        iso639_1: "ro",
+        iso639_3: "ron",
+        aliases: &["rum", "moldavian", "română", "romana"],
+        script: "Latin",
+        has_exceptions: false,
+    },
+    Language {
+        name: "russian",
+        algorithm: Algorithm::Russian,
+        iso639_1: "ru",
+        iso639_3: "rus",
+        aliases: &["русский"],
+        script: "Cyrillic",
+        has_exceptions: false,
+    },
+    Language {
+        name: "spanish",
+        algorithm: Algorithm::Spanish,
+        iso639_1: "es",
+        iso639_3: "spa",
+        aliases: &["castilian", "español", "espanol"],
+        script: "Latin",
+        has_exceptions: false,
+    },
+    Language {
+        name: "swedish",
+        algorithm: Algorithm::Swedish,
+        iso639_1: "sv",
+        iso639_3: "swe",
+        aliases: &["svenska"],
+        script: "Latin",
+        has_exceptions: false,
+    },
+    Language {
+        name: "tamil",
+        algorithm: Algorithm::Tamil,
+        iso639_1: "ta",
+        iso639_3: "tam",
+        aliases: &["தமிழ்"],
+        script: "Tamil",
+        has_exceptions: false,
+    },
+    Language {
+        name: "turkish",
+        algorithm: Algorithm::Turkish,
+        iso639_1: "tr",
+        iso639_3: "tur",
+        aliases: &["türkçe", "turkce"],
+        script: "Latin",
+        has_exceptions: false,
+    },
+];
+
+impl Language {
+    fn answers_to(&self, name: &str) -> bool {
+        self.name == name
+            || self.iso639_1 == name
+            || self.iso639_3 == name
+            || self.aliases.contains(&name)
+    }
+}
+
+fn find(name: &str) -> Option<&'static Language> {
+    LANGUAGES.iter().find(|language| language.answers_to(name))
+}
+
+// Resolve a language name, code, alias or locale tag (case-insensitive)
+pub fn resolve(lang: &str) -> PyResult<&'static Language> {
+    let normalized = lang.trim().to_lowercase().replace('_', "-");
+    find(&normalized)
+        .or_else(|| {
+            let (primary, _region) = normalized.split_once('-')?;
+            find(primary)
+        })
+        .ok_or_else(|| PyValueError::new_err(format!("Unsupported language: {}", lang)))
+}
+
+pub fn language_of(algorithm: Algorithm) -> &'static Language {
+    LANGUAGES
+        .iter()
+        .find(|language| language.algorithm == algorithm)
+        .expect("every algorithm has a language entry")
+}
+
+// Metadata of a supported language, as returned by `supported_languages()`
+#[pyclass(module = "py_rust_stemmers_tuned", frozen, get_all)]
+pub struct LanguageInfo {
+    pub name: &'static str,
+    pub iso639_1: &'static str,
+    pub iso639_3: &'static str,
+    pub aliases: Vec<&'static str>,
+    pub script: &'static str,
+    pub has_stopwords: bool,
+    pub has_exceptions: bool,
+}
+
+#[pymethods]
+impl LanguageInfo {
+    fn __repr__(&self) -> String {
+        format!(
+            "LanguageInfo(name='{}', iso639_1='{}', iso639_3='{}', script='{}')",
+            self.name, self.iso639_1, self.iso639_3, self.script
+        )
+    }
+}
+
+impl From<&Language> for LanguageInfo {
+    fn from(language: &Language) -> Self {
+        LanguageInfo {
+            name: language.name,
+            iso639_1: language.iso639_1,
+            iso639_3: language.iso639_3,
+            aliases: language.aliases.to_vec(),
+            script: language.script,
+            has_stopwords: stopwords::has_builtin(language.algorithm),
+            has_exceptions: language.has_exceptions,
+        }
+    }
+}
+
+// The script of a token, from its first letter in one of the scripts our
+// languages are written in. `None` for numbers, punctuation and scripts we
+// have no algorithm for.
+pub fn script_of(word: &str) -> Option<&'static str> {
+    word.chars()
+        .filter(|c| c.is_alphabetic())
+        .find_map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '\u{00C0}'..='\u{024F}' | '\u{1E00}'..='\u{1EFF}' => {
+                Some("Latin")
+            }
+            '\u{0370}'..='\u{03FF}' | '\u{1F00}'..='\u{1FFF}' => Some("Greek"),
+            '\u{0400}'..='\u{052F}' | '\u{1C80}'..='\u{1C8F}' | '\u{A640}'..='\u{A69F}' => {
+                Some("Cyrillic")
+            }
+            '\u{0600}'..='\u{06FF}'
+            | '\u{0750}'..='\u{077F}'
+            | '\u{08A0}'..='\u{08FF}'
+            | '\u{FB50}'..='\u{FDFF}'
+            | '\u{FE70}'..='\u{FEFF}' => Some("Arabic"),
+            '\u{0900}'..='\u{097F}' | '\u{A8E0}'..='\u{A8FF}' => Some("Devanagari"),
+            '\u{0B80}'..='\u{0BFF}' => Some("Tamil"),
+            _ => None,
+        })
+}
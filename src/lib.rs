@@ -1,20 +1,48 @@
-use dashmap::DashMap;
+mod admission;
+mod algorithms;
+// Public only for the benchmarks in `benches/`
+#[doc(hidden)]
+pub mod cache;
+mod classify;
+mod detect;
+mod l1;
+mod languages;
+mod multi;
+mod namespace;
+mod normalize;
+mod overrides;
+mod persist;
+mod recorder;
+mod snowball;
+mod stopwords;
+mod tokenize;
+mod warmup;
+
+#[doc(hidden)]
+pub use admission::Admission;
+use admission::AdmissionArg;
+use algorithms::{Algorithm, Stemmer};
+use cache::{CacheSession, CacheStats, StemCache, DEFAULT_MAX_ENTRIES};
+use classify::{Action, TokenClass, TokenPolicy};
+use detect::{AutoStemmer, LanguageDetection};
+use languages::LanguageInfo;
+use multi::MultiLanguageStemmer;
+use namespace::{default_cache, CacheArg, PyStemCache, DEFAULT_NAMESPACE};
+use normalize::Normalizer;
+use overrides::{Overrides, OverridesArg, ProtectedArg};
 use pyo3::prelude::*;
+use pyo3::types::PyTuple;
+use pyo3::IntoPyObjectExt;
 use rayon::prelude::*;
-use rust_stemmers::{Algorithm, Stemmer};
+use recorder::StemRecorder;
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
 use std::sync::Arc;
-use std::sync::OnceLock;
-
-// Use DashMap for lock-free reads and sharded writes
-type CacheKey = (u8, String);
-type SharedCache = Arc<DashMap<CacheKey, String>>;
-
-static STEM_CACHE: OnceLock<SharedCache> = OnceLock::new();
-
-// Initialize cache with optimal capacity
-fn get_cache() -> &'static SharedCache {
-    STEM_CACHE.get_or_init(|| Arc::new(DashMap::with_capacity_and_shard_amount(100_000, 32)))
-}
+use stopwords::{Stopwords, StopwordsArg};
+use tokenize::{OffsetCounter, StemmedToken, Token, TokenKind, Tokenizer, DEFAULT_TOKENIZER};
+use warmup::{CacheWarmup, Warmup};
 
 // Convert Algorithm to u8 discriminant (compile-time optimized)
 const fn algorithm_to_u8(algorithm: Algorithm) -> u8 {
@@ -37,70 +65,615 @@ const fn algorithm_to_u8(algorithm: Algorithm) -> u8 {
         Algorithm::Swedish => 15,
         Algorithm::Tamil => 16,
         Algorithm::Turkish => 17,
+        // Not in rust-stemmers, so numbered after its algorithms
+        Algorithm::Hindi => 18,
+        Algorithm::Indonesian => 19,
+        Algorithm::Custom(id) => id,
     }
 }
 
+// Inverse of `algorithm_to_u8`, for discriminants read back from outside
+const fn algorithm_from_u8(discriminant: u8) -> Option<Algorithm> {
+    Some(match discriminant {
+        0 => Algorithm::Arabic,
+        1 => Algorithm::Danish,
+        2 => Algorithm::Dutch,
+        3 => Algorithm::English,
+        4 => Algorithm::Finnish,
+        5 => Algorithm::French,
+        6 => Algorithm::German,
+        7 => Algorithm::Greek,
+        8 => Algorithm::Hungarian,
+        9 => Algorithm::Italian,
+        10 => Algorithm::Norwegian,
+        11 => Algorithm::Portuguese,
+        12 => Algorithm::Romanian,
+        13 => Algorithm::Russian,
+        14 => Algorithm::Spanish,
+        15 => Algorithm::Swedish,
+        16 => Algorithm::Tamil,
+        17 => Algorithm::Turkish,
+        18 => Algorithm::Hindi,
+        19 => Algorithm::Indonesian,
+        _ => return None,
+    })
+}
+
+// Map a language name, ISO 639 code, alias or locale tag to its algorithm
+fn parse_language(lang: &str) -> PyResult<Algorithm> {
+    Ok(languages::resolve(lang)?.algorithm)
+}
+
 // Optimized stemmer with thread-local caching
-#[pyclass]
+#[pyclass(module = "py_rust_stemmers_tuned")]
 pub struct SnowballStemmer {
     algorithm: Algorithm,
-    use_cache: bool,
+    // Built once and shared by every call and rayon worker
+    stemmer: Stemmer,
+    // `None` when caching is disabled
+    cache: Option<Py<PyStemCache>>,
+    admission: Admission,
+    // Applied to every input before the cache lookup
+    normalizer: Normalizer,
+    stopwords: Stopwords,
+    // Protected words and stem overrides, checked before the cache
+    overrides: Overrides,
+    // Counts the surface forms behind each stem when set
+    recorder: Option<Py<StemRecorder>>,
+    // What to do with numbers, URLs, emails, mentions, hashtags and emoji
+    token_policy: TokenPolicy,
+}
+
+impl SnowballStemmer {
+    fn with_algorithm(
+        algorithm: Algorithm,
+        cache: Option<Py<PyStemCache>>,
+        admission: Admission,
+    ) -> Self {
+        SnowballStemmer {
+            algorithm,
+            stemmer: Stemmer::create(algorithm),
+            cache,
+            admission,
+            normalizer: Normalizer::default(),
+            stopwords: Stopwords::default(),
+            overrides: Overrides::default(),
+            recorder: None,
+            token_policy: TokenPolicy::NONE,
+        }
+    }
+
+    #[inline(always)]
+    fn shared_cache(&self) -> Option<&Arc<StemCache>> {
+        self.cache.as_ref().map(|cache| cache.get().shared())
+    }
+
+    // Compile `source` as the program `name`. `origin` (its file, usually)
+    // prefixes syntax errors.
+    fn from_snowball_program(
+        py: Python<'_>,
+        name: &str,
+        source: String,
+        origin: &str,
+        cache: CacheArg,
+        admission: AdmissionArg,
+    ) -> PyResult<Self> {
+        let algorithm = py
+            .detach(|| algorithms::load_snowball(name, source))
+            .map_err(|message| {
+                pyo3::exceptions::PyValueError::new_err(format!("{}: {}", origin, message))
+            })?;
+        Ok(SnowballStemmer::with_algorithm(
+            algorithm,
+            cache.resolve(py)?,
+            admission.resolve()?,
+        ))
+    }
+
+    // Applies the `case`, `normalize` and `orthography` constructor options
+    fn with_normalization(
+        self,
+        case: Option<&str>,
+        normalize: Option<&str>,
+        orthography: bool,
+    ) -> PyResult<Self> {
+        let normalizer =
+            Normalizer::parse(case, normalize)?.with_orthography(self.algorithm, orthography);
+        Ok(SnowballStemmer { normalizer, ..self })
+    }
+
+    // Applies the `stopwords` and `extra_stopwords` constructor options
+    fn with_stopwords(
+        self,
+        stopwords: StopwordsArg<'_>,
+        extra: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<Self> {
+        let stopwords = Stopwords::new(self.algorithm, stopwords, extra)?;
+        Ok(SnowballStemmer { stopwords, ..self })
+    }
+
+    // Applies the `protected` and `overrides` constructor options, after the
+    // normalization options that their words are normalized with
+    fn with_overrides(
+        self,
+        py: Python<'_>,
+        protected: Option<ProtectedArg<'_>>,
+        stems: Option<OverridesArg>,
+    ) -> PyResult<Self> {
+        let overrides = Overrides::new(py, self.normalizer, protected, stems)?;
+        Ok(SnowballStemmer { overrides, ..self })
+    }
+
+    // Applies the `recorder` and `token_policy` constructor options
+    fn with_recorder(
+        self,
+        recorder: Option<Py<StemRecorder>>,
+        token_policy: Option<TokenPolicy>,
+    ) -> Self {
+        SnowballStemmer {
+            recorder,
+            token_policy: token_policy.unwrap_or(TokenPolicy::NONE),
+            ..self
+        }
+    }
+
+    // Drops stopwords and the tokens the token policy drops from `words`
+    fn without_dropped(&self, mut words: Vec<String>) -> Vec<String> {
+        if self.stopwords.filter || !self.token_policy.is_empty() {
+            words.retain(|word| !self.drops(word));
+        }
+        words
+    }
+
+    // Whether `word` is a stopword or a token the token policy drops
+    fn drops(&self, word: &str) -> bool {
+        self.stopwords.filter && self.stopwords.matches(self.normalizer, word)
+            || self.token_policy.drops(word)
+    }
+
+    // Store the stem of `word` in `cache` under the key the stemming methods
+    // would look it up by, without counting a lookup, and return whether it
+    // was stored. Words those methods drop, keep as they are or take from the
+    // overrides never reach the cache, so they are skipped.
+    fn warm_word(&self, cache: &StemCache, word: &str) -> bool {
+        if self.drops(word) {
+            return false;
+        }
+        let word = match self.token_policy.classify(word) {
+            Some(class) => match self.token_policy.action(class, word) {
+                Action::Stem(word) => word,
+                Action::Drop | Action::Verbatim(_) => return false,
+            },
+            None => Cow::Borrowed(word),
+        };
+        let word = &*self.normalizer.apply(&word);
+        if self.overrides.get(word).is_some() {
+            return false;
+        }
+        let stem = self.stemmer.stem(word).into_owned();
+        cache.insert(
+            algorithm_to_u8(self.algorithm),
+            word,
+            stem,
+            Admission::Always,
+        );
+        true
+    }
+
+    // Stem `word`, unless the token policy says otherwise for its class
+    #[inline(always)]
+    fn stem_input(&self, session: Option<&mut CacheSession<'_>>, word: &str) -> String {
+        match self.token_policy.classify(word) {
+            // Only `stem_word` sees tokens that are dropped; it returns them as given
+            Some(class) => self
+                .apply_policy(session, class, word)
+                .unwrap_or_else(|| word.to_owned()),
+            None => self.stem_cached(session, word),
+        }
+    }
+
+    // The result for a token of `class`, `None` when it is dropped
+    fn apply_policy(
+        &self,
+        session: Option<&mut CacheSession<'_>>,
+        class: TokenClass,
+        word: &str,
+    ) -> Option<String> {
+        match self.token_policy.action(class, word) {
+            Action::Drop => None,
+            Action::Verbatim(token) => Some(token.into_owned()),
+            Action::Stem(word) => Some(self.stem_cached(session, &word)),
+        }
+    }
+
+    // Stem one word through `session`, or directly when caching is disabled,
+    // and record it as a surface form of its stem if asked to
+    #[inline(always)]
+    fn stem_cached(&self, session: Option<&mut CacheSession<'_>>, word: &str) -> String {
+        self.stem_recorded(session, word, word)
+    }
+
+    // As `stem_cached`, recording `form`: the token as it appears in the
+    // text, for words the tokenizer has lowercased
+    #[inline(always)]
+    fn stem_recorded(
+        &self,
+        session: Option<&mut CacheSession<'_>>,
+        word: &str,
+        form: &str,
+    ) -> String {
+        let stem = self.stem_normalized(session, word);
+        if let Some(recorder) = &self.recorder {
+            recorder.get().record(form, &stem);
+        }
+        stem
+    }
+
+    #[inline(always)]
+    fn stem_normalized(&self, session: Option<&mut CacheSession<'_>>, word: &str) -> String {
+        let word = &*self.normalizer.apply(word);
+        // Checked before the cache, so that these stems stay out of it
+        if let Some(stem) = self.overrides.get(word) {
+            return stem.to_owned();
+        }
+        cache::stem_through(
+            session,
+            algorithm_to_u8(self.algorithm),
+            &self.stemmer,
+            word,
+            self.admission,
+        )
+    }
+
+    // Tokenize `text` and stem its words; numbers are kept as they are, and
+    // the classes of token the token policy covers are handled as it says.
+    // `emit` turns each token and its stem into an output item.
+    fn stem_tokens<T>(
+        &self,
+        mut session: Option<&mut CacheSession<'_>>,
+        tokenizer: &Tokenizer,
+        text: &str,
+        mut emit: impl FnMut(&Token<'_>, String) -> T,
+    ) -> Vec<T> {
+        tokenizer
+            .tokens_with(text, &self.token_policy)
+            .filter_map(|token| {
+                let stem = match token.kind {
+                    TokenKind::Word
+                        if self.stopwords.filter
+                            && self.stopwords.matches(self.normalizer, &token.text) =>
+                    {
+                        return None
+                    }
+                    TokenKind::Word => {
+                        self.stem_recorded(session.as_deref_mut(), &token.text, token.surface)
+                    }
+                    TokenKind::Number => token.text.to_string(),
+                    TokenKind::Special(class) => {
+                        self.apply_policy(session.as_deref_mut(), class, &token.text)?
+                    }
+                };
+                Some(emit(&token, stem))
+            })
+            .collect()
+    }
+
+    fn stem_tokens_with_offsets(
+        &self,
+        session: Option<&mut CacheSession<'_>>,
+        tokenizer: &Tokenizer,
+        text: &str,
+    ) -> Vec<StemmedToken> {
+        let mut offsets = OffsetCounter::new(text);
+        self.stem_tokens(session, tokenizer, text, |token, stem| {
+            StemmedToken::new(token, stem, &mut offsets)
+        })
+    }
+
+    fn warmup_cache(&self) -> PyResult<Arc<StemCache>> {
+        self.shared_cache().cloned().ok_or_else(|| {
+            pyo3::exceptions::PyValueError::new_err(
+                "Cache warm-up needs a stemmer created with caching enabled",
+            )
+        })
+    }
 }
 
 #[pymethods]
 impl SnowballStemmer {
+    // `case` ("lower" or "fold"), `normalize` ("NFC" or "NFKC") and the
+    // language's `orthography` rules are applied to inputs before the cache
+    // lookup, so equivalent spellings share an entry. With `stopwords`, the
+    // stemming methods drop the built-in stopwords, or the given ones instead.
     #[new]
-    #[pyo3(signature = (lang, cache = true))]
-    fn new(lang: &str, cache: bool) -> PyResult<Self> {
-        let algorithm = match lang.to_lowercase().as_str() {
-            "arabic" => Algorithm::Arabic,
-            "danish" => Algorithm::Danish,
-            "dutch" => Algorithm::Dutch,
-            "english" => Algorithm::English,
-            "finnish" => Algorithm::Finnish,
-            "french" => Algorithm::French,
-            "german" => Algorithm::German,
-            "greek" => Algorithm::Greek,
-            "hungarian" => Algorithm::Hungarian,
-            "italian" => Algorithm::Italian,
-            "norwegian" => Algorithm::Norwegian,
-            "portuguese" => Algorithm::Portuguese,
-            "romanian" => Algorithm::Romanian,
-            "russian" => Algorithm::Russian,
-            "spanish" => Algorithm::Spanish,
-            "swedish" => Algorithm::Swedish,
-            "tamil" => Algorithm::Tamil,
-            "turkish" => Algorithm::Turkish,
-            _ => {
-                return Err(pyo3::exceptions::PyValueError::new_err(format!(
-                    "Unsupported language: {}",
-                    lang
-                )))
-            }
+    #[pyo3(signature = (
+        lang,
+        cache = CacheArg::Enabled(true),
+        admission = AdmissionArg::Sightings(1),
+        case = None,
+        normalize = None,
+        orthography = false,
+        stopwords = StopwordsArg::Enabled(false),
+        extra_stopwords = None,
+        protected = None,
+        overrides = None,
+        recorder = None,
+        token_policy = None,
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        py: Python<'_>,
+        lang: &str,
+        cache: CacheArg,
+        admission: AdmissionArg,
+        case: Option<&str>,
+        normalize: Option<&str>,
+        orthography: bool,
+        stopwords: StopwordsArg<'_>,
+        extra_stopwords: Option<Bound<'_, PyAny>>,
+        protected: Option<ProtectedArg<'_>>,
+        overrides: Option<OverridesArg>,
+        recorder: Option<Py<StemRecorder>>,
+        token_policy: Option<TokenPolicy>,
+    ) -> PyResult<Self> {
+        SnowballStemmer::with_algorithm(
+            parse_language(lang)?,
+            cache.resolve(py)?,
+            admission.resolve()?,
+        )
+        .with_normalization(case, normalize, orthography)?
+        .with_stopwords(stopwords, extra_stopwords.as_ref())?
+        .with_overrides(py, protected, overrides)
+        .map(|stemmer| stemmer.with_recorder(recorder, token_policy))
+    }
+
+    // A stemmer running the Snowball program in the `.sbl` file at `path`,
+    // interpreted at runtime. Its `language` is the file name without extension.
+    #[staticmethod]
+    #[pyo3(signature = (
+        path,
+        cache = CacheArg::Enabled(true),
+        admission = AdmissionArg::Sightings(1),
+        case = None,
+        normalize = None,
+        orthography = false,
+        stopwords = StopwordsArg::Enabled(false),
+        extra_stopwords = None,
+        protected = None,
+        overrides = None,
+        recorder = None,
+        token_policy = None,
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn from_snowball_source(
+        py: Python<'_>,
+        path: PathBuf,
+        cache: CacheArg,
+        admission: AdmissionArg,
+        case: Option<&str>,
+        normalize: Option<&str>,
+        orthography: bool,
+        stopwords: StopwordsArg<'_>,
+        extra_stopwords: Option<Bound<'_, PyAny>>,
+        protected: Option<ProtectedArg<'_>>,
+        overrides: Option<OverridesArg>,
+        recorder: Option<Py<StemRecorder>>,
+        token_policy: Option<TokenPolicy>,
+    ) -> PyResult<Self> {
+        let source = std::fs::read_to_string(&path)?;
+        let name = path
+            .file_stem()
+            .map_or_else(|| "snowball".into(), |stem| stem.to_string_lossy());
+        SnowballStemmer::from_snowball_program(
+            py,
+            &name,
+            source,
+            &path.display().to_string(),
+            cache,
+            admission,
+        )?
+        .with_normalization(case, normalize, orthography)?
+        .with_stopwords(stopwords, extra_stopwords.as_ref())?
+        .with_overrides(py, protected, overrides)
+        .map(|stemmer| stemmer.with_recorder(recorder, token_policy))
+    }
+
+    // Rebuilds an unpickled stemmer running a program loaded from source
+    #[staticmethod]
+    #[allow(clippy::too_many_arguments)]
+    fn _from_snowball_program(
+        py: Python<'_>,
+        program: (String, String),
+        cache: CacheArg,
+        admission: AdmissionArg,
+        case: Option<&str>,
+        normalize: Option<&str>,
+        orthography: bool,
+        stopwords: StopwordsArg<'_>,
+        extra_stopwords: Option<Bound<'_, PyAny>>,
+        protected: Option<ProtectedArg<'_>>,
+        overrides: Option<OverridesArg>,
+        recorder: Option<Py<StemRecorder>>,
+        token_policy: Option<TokenPolicy>,
+    ) -> PyResult<Self> {
+        let (name, source) = program;
+        SnowballStemmer::from_snowball_program(py, &name, source, &name, cache, admission)?
+            .with_normalization(case, normalize, orthography)?
+            .with_stopwords(stopwords, extra_stopwords.as_ref())?
+            .with_overrides(py, protected, overrides)
+            .map(|stemmer| stemmer.with_recorder(recorder, token_policy))
+    }
+
+    // Pickled as the arguments it was created with. The cache goes by
+    // reference, so stemmers sharing a cache still share one after unpickling.
+    fn __reduce__<'py>(
+        &self,
+        py: Python<'py>,
+    ) -> PyResult<(Bound<'py, PyAny>, Bound<'py, PyTuple>)> {
+        let cache = match &self.cache {
+            Some(cache) => cache.bind(py).clone().into_any(),
+            None => false.into_bound_py_any(py)?,
         };
-        Ok(SnowballStemmer {
-            algorithm,
-            use_cache: cache,
+        let admission = AdmissionArg::from(self.admission).into_bound_py_any(py)?;
+        let case = self.normalizer.case_name().into_bound_py_any(py)?;
+        let normalize = self.normalizer.form_name().into_bound_py_any(py)?;
+        let orthography = self
+            .normalizer
+            .orthography
+            .is_some()
+            .into_bound_py_any(py)?;
+        let stopwords = match self.stopwords.replacement() {
+            Some(words) => words.into_bound_py_any(py)?,
+            None => self.stopwords.filter.into_bound_py_any(py)?,
+        };
+        let extra_stopwords = self.stopwords.extra().into_bound_py_any(py)?;
+        let protected = self.overrides.protected().into_bound_py_any(py)?;
+        let overrides = self.overrides.stems().into_bound_py_any(py)?;
+        let recorder = self.recorder.as_ref().into_bound_py_any(py)?;
+        let token_policy = (!self.token_policy.is_empty())
+            .then_some(self.token_policy)
+            .into_bound_py_any(py)?;
+        let class = py.get_type::<SnowballStemmer>();
+        Ok(match self.algorithm {
+            Algorithm::Custom(id) => {
+                let args = [
+                    algorithms::custom_source(id).into_bound_py_any(py)?,
+                    cache,
+                    admission,
+                    case,
+                    normalize,
+                    orthography,
+                    stopwords,
+                    extra_stopwords,
+                    protected,
+                    overrides,
+                    recorder,
+                    token_policy,
+                ];
+                (
+                    class.getattr("_from_snowball_program")?,
+                    PyTuple::new(py, args)?,
+                )
+            }
+            algorithm => {
+                let name = languages::language_of(algorithm).name;
+                let args = [
+                    name.into_bound_py_any(py)?,
+                    cache,
+                    admission,
+                    case,
+                    normalize,
+                    orthography,
+                    stopwords,
+                    extra_stopwords,
+                    protected,
+                    overrides,
+                    recorder,
+                    token_policy,
+                ];
+                (class.into_any(), PyTuple::new(py, args)?)
+            }
         })
     }
 
-    #[inline(always)]
-    fn stem_word(&self, input: &str) -> String {
-        if !self.use_cache {
-            return Stemmer::create(self.algorithm).stem(input).into_owned();
-        }
+    // Stemmers are equal when they run the same algorithm into the same
+    // cache with the same admission policy, input normalization, stopwords,
+    // overrides, recorder and token policy
+    fn __eq__(&self, other: &Self) -> bool {
+        let same_cache = match (self.shared_cache(), other.shared_cache()) {
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        };
+        let same_recorder = match (&self.recorder, &other.recorder) {
+            (Some(a), Some(b)) => a.is(b),
+            (None, None) => true,
+            _ => false,
+        };
+        self.algorithm == other.algorithm
+            && self.admission == other.admission
+            && self.normalizer == other.normalizer
+            && self.stopwords == other.stopwords
+            && self.overrides == other.overrides
+            && self.token_policy == other.token_policy
+            && same_cache
+            && same_recorder
+    }
 
-        let cache_key = (algorithm_to_u8(self.algorithm), input.to_string());
+    fn __hash__(&self) -> u64 {
+        let mut hasher = std::hash::DefaultHasher::new();
+        algorithm_to_u8(self.algorithm).hash(&mut hasher);
+        self.admission.hash(&mut hasher);
+        self.normalizer.hash(&mut hasher);
+        self.stopwords.filter.hash(&mut hasher);
+        self.stopwords.replacement_len().hash(&mut hasher);
+        self.stopwords.extra_len().hash(&mut hasher);
+        self.overrides.protected_len().hash(&mut hasher);
+        self.overrides.stems_len().hash(&mut hasher);
+        self.shared_cache().map(Arc::as_ptr).hash(&mut hasher);
+        self.recorder.as_ref().map(Py::as_ptr).hash(&mut hasher);
+        self.token_policy.hash(&mut hasher);
+        hasher.finish()
+    }
 
-        // Fast path: try to read from cache without cloning
-        if let Some(entry) = get_cache().get(&cache_key) {
-            return entry.value().clone();
+    // Arguments left at their defaults are omitted
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        let mut repr = match self.algorithm {
+            Algorithm::Custom(_) => format!(
+                "SnowballStemmer.from_snowball_source(<{:?} program>",
+                self.language()
+            ),
+            _ => format!("SnowballStemmer({:?}", self.language()),
+        };
+        match &self.cache {
+            None => repr.push_str(", cache=False"),
+            Some(cache) if cache.get().name() == Some(DEFAULT_NAMESPACE) => {}
+            Some(cache) => repr.push_str(&format!(", cache={}", cache.bind(py).repr()?)),
+        }
+        match AdmissionArg::from(self.admission) {
+            AdmissionArg::Policy(policy) if policy == "always" => {}
+            AdmissionArg::Policy(policy) => repr.push_str(&format!(", admission={:?}", policy)),
+            AdmissionArg::Sightings(n) => repr.push_str(&format!(", admission={}", n)),
+        }
+        if let Some(case) = self.normalizer.case_name() {
+            repr.push_str(&format!(", case={:?}", case));
+        }
+        if let Some(form) = self.normalizer.form_name() {
+            repr.push_str(&format!(", normalize={:?}", form));
+        }
+        if self.normalizer.orthography.is_some() {
+            repr.push_str(", orthography=True");
+        }
+        let count = |len: usize| format!("<{} word{}>", len, if len == 1 { "" } else { "s" });
+        match self.stopwords.replacement_len() {
+            Some(len) => repr.push_str(&format!(", stopwords={}", count(len))),
+            None if self.stopwords.filter => repr.push_str(", stopwords=True"),
+            None => {}
+        }
+        if let Some(len) = self.stopwords.extra_len() {
+            repr.push_str(&format!(", extra_stopwords={}", count(len)));
+        }
+        if let Some(len) = self.overrides.protected_len() {
+            repr.push_str(&format!(", protected={}", count(len)));
         }
+        if let Some(len) = self.overrides.stems_len() {
+            repr.push_str(&format!(", overrides={}", count(len)));
+        }
+        if let Some(recorder) = &self.recorder {
+            repr.push_str(&format!(", recorder={}", recorder.bind(py).repr()?));
+        }
+        if !self.token_policy.is_empty() {
+            let token_policy = self.token_policy.into_pyobject(py)?;
+            repr.push_str(&format!(", token_policy={}", token_policy.repr()?));
+        }
+        repr.push(')');
+        Ok(repr)
+    }
 
-        // Cache miss: stem and cache
-        let result = Stemmer::create(self.algorithm).stem(input).into_owned();
-        get_cache().insert(cache_key, result.clone());
-        result
+    #[inline(always)]
+    fn stem_word(&self, input: &str) -> String {
+        let mut session = self.shared_cache().map(|cache| cache.session());
+        self.stem_input(session.as_mut(), input)
     }
 
     #[inline(always)]
@@ -109,75 +682,312 @@ impl SnowballStemmer {
         py: Python<'_>,
         inputs: Vec<String>,
     ) -> PyResult<Vec<String>> {
-        let algorithm = self.algorithm;
-        let use_cache = self.use_cache;
+        let cache = self.shared_cache();
 
         let result = py.detach(|| {
-            if !use_cache {
+            let inputs = self.without_dropped(inputs);
+            let Some(cache) = cache else {
                 // Fast path without cache
                 return inputs
                     .par_iter()
                     .with_min_len(500) // Increased chunk size for better throughput
-                    .map(|word| Stemmer::create(algorithm).stem(word).into_owned())
+                    .map(|word| self.stem_input(None, word))
                     .collect::<Vec<String>>();
-            }
-
-            // Cache-enabled path with batching
-            let cache = get_cache();
-            let algorithm_discriminant = algorithm_to_u8(algorithm);
+            };
 
+            // Cache-enabled path: each worker reads its L1, then the shared shards
             inputs
                 .par_iter()
                 .with_min_len(250) // Optimal for cache-heavy workload
-                .map(|word| {
-                    let cache_key = (algorithm_discriminant, word.clone());
-
-                    // Try read-only lookup first
-                    if let Some(entry) = cache.get(&cache_key) {
-                        return entry.value().clone();
-                    }
-
-                    // Cache miss: compute and store
-                    let result = Stemmer::create(algorithm).stem(word).into_owned();
-                    cache.insert(cache_key, result.clone());
-                    result
-                })
+                .map_init(
+                    || cache.session(),
+                    |session, word| self.stem_input(Some(session), word),
+                )
                 .collect::<Vec<String>>()
         });
         Ok(result)
     }
 
-    #[inline(always)]
-    pub fn stem_words(&self, inputs: Vec<String>) -> Vec<String> {
-        if !self.use_cache {
-            return inputs
-                .iter()
-                .map(|word| Stemmer::create(self.algorithm).stem(word).into_owned())
-                .collect();
+    // Split `text` into words (UAX #29) and stem them, in text order
+    #[pyo3(signature = (text, tokenizer = None))]
+    fn stem_text(
+        &self,
+        py: Python<'_>,
+        text: &str,
+        tokenizer: Option<Py<Tokenizer>>,
+    ) -> Vec<String> {
+        let cache = self.shared_cache();
+        py.detach(|| {
+            let tokenizer = tokenizer.as_ref().map_or(&DEFAULT_TOKENIZER, Py::get);
+            match cache {
+                Some(cache) => {
+                    self.stem_tokens(Some(&mut cache.session()), tokenizer, text, |_, stem| stem)
+                }
+                None => self.stem_tokens(None, tokenizer, text, |_, stem| stem),
+            }
+        })
+    }
+
+    // `stem_text` for many texts, in parallel across texts
+    #[pyo3(signature = (texts, tokenizer = None))]
+    fn stem_texts(
+        &self,
+        py: Python<'_>,
+        texts: Vec<String>,
+        tokenizer: Option<Py<Tokenizer>>,
+    ) -> Vec<Vec<String>> {
+        let cache = self.shared_cache();
+        py.detach(|| {
+            let tokenizer = tokenizer.as_ref().map_or(&DEFAULT_TOKENIZER, Py::get);
+            let Some(cache) = cache else {
+                return texts
+                    .par_iter()
+                    .map(|text| self.stem_tokens(None, tokenizer, text, |_, stem| stem))
+                    .collect();
+            };
+            texts
+                .par_iter()
+                .map_init(
+                    || cache.session(),
+                    |session, text| {
+                        self.stem_tokens(Some(session), tokenizer, text, |_, stem| stem)
+                    },
+                )
+                .collect()
+        })
+    }
+
+    // Like `stem_text`, with each token's surface form and its offsets into
+    // `text`, for highlighting
+    #[pyo3(signature = (text, tokenizer = None))]
+    fn stem_text_with_offsets(
+        &self,
+        py: Python<'_>,
+        text: &str,
+        tokenizer: Option<Py<Tokenizer>>,
+    ) -> Vec<StemmedToken> {
+        let cache = self.shared_cache();
+        py.detach(|| {
+            let tokenizer = tokenizer.as_ref().map_or(&DEFAULT_TOKENIZER, Py::get);
+            match cache {
+                Some(cache) => {
+                    self.stem_tokens_with_offsets(Some(&mut cache.session()), tokenizer, text)
+                }
+                None => self.stem_tokens_with_offsets(None, tokenizer, text),
+            }
+        })
+    }
+
+    // `stem_text_with_offsets` for many texts, in parallel across texts
+    #[pyo3(signature = (texts, tokenizer = None))]
+    fn stem_texts_with_offsets(
+        &self,
+        py: Python<'_>,
+        texts: Vec<String>,
+        tokenizer: Option<Py<Tokenizer>>,
+    ) -> Vec<Vec<StemmedToken>> {
+        let cache = self.shared_cache();
+        py.detach(|| {
+            let tokenizer = tokenizer.as_ref().map_or(&DEFAULT_TOKENIZER, Py::get);
+            let Some(cache) = cache else {
+                return texts
+                    .par_iter()
+                    .map(|text| self.stem_tokens_with_offsets(None, tokenizer, text))
+                    .collect();
+            };
+            texts
+                .par_iter()
+                .map_init(
+                    || cache.session(),
+                    |session, text| self.stem_tokens_with_offsets(Some(session), tokenizer, text),
+                )
+                .collect()
+        })
+    }
+
+    // Stem a vocabulary into this stemmer's cache. With `background=True` the
+    // work happens on a separate thread and a `CacheWarmup` handle is returned.
+    #[pyo3(signature = (words, background = false))]
+    fn warm_cache(slf: &Bound<'_, Self>, words: Vec<String>, background: bool) -> PyResult<Warmup> {
+        let cache = slf.borrow().warmup_cache()?;
+        let task = move |stemmer: &SnowballStemmer| Ok(warmup::warm(stemmer, &cache, &words));
+        if background {
+            return Ok(Warmup::Background(CacheWarmup::spawn(
+                slf.clone().unbind(),
+                task,
+            )));
+        }
+        let stemmer = slf.borrow();
+        let stemmer: &SnowballStemmer = &stemmer;
+        slf.py().detach(|| task(stemmer)).map(Warmup::Done)
+    }
+
+    // Like `warm_cache`, reading one word per line from a file
+    #[pyo3(signature = (path, background = false))]
+    fn warm_cache_from_file(
+        slf: &Bound<'_, Self>,
+        path: PathBuf,
+        background: bool,
+    ) -> PyResult<Warmup> {
+        let cache = slf.borrow().warmup_cache()?;
+        let task = move |stemmer: &SnowballStemmer| {
+            let words = warmup::read_vocabulary(&path)?;
+            Ok(warmup::warm(stemmer, &cache, &words))
+        };
+        if background {
+            return Ok(Warmup::Background(CacheWarmup::spawn(
+                slf.clone().unbind(),
+                task,
+            )));
+        }
+        let stemmer = slf.borrow();
+        let stemmer: &SnowballStemmer = &stemmer;
+        slf.py().detach(|| task(stemmer)).map(Warmup::Done)
+    }
+
+    // Canonical name of the language the stemmer was created for, or the
+    // file name of a program loaded with `from_snowball_source`
+    #[getter]
+    fn language(&self) -> String {
+        match self.algorithm {
+            Algorithm::Custom(id) => algorithms::custom_name(id),
+            algorithm => languages::language_of(algorithm).name.to_owned(),
         }
+    }
+
+    // The cache this stemmer reads and fills, `None` when caching is disabled
+    #[getter]
+    fn cache(&self, py: Python<'_>) -> Option<Py<PyStemCache>> {
+        self.cache.as_ref().map(|cache| cache.clone_ref(py))
+    }
+
+    // The recorder counting this stemmer's surface forms, if any
+    #[getter]
+    fn recorder(&self, py: Python<'_>) -> Option<Py<StemRecorder>> {
+        self.recorder
+            .as_ref()
+            .map(|recorder| recorder.clone_ref(py))
+    }
+
+    // The stopword list used by `filter_stopwords`, and by the stemming
+    // methods when created with `stopwords`
+    #[getter]
+    fn stopwords(&self) -> HashSet<String> {
+        self.stopwords.words()
+    }
+
+    fn is_stopword(&self, word: &str) -> bool {
+        self.stopwords.matches(self.normalizer, word)
+    }
 
-        let cache = get_cache();
-        let algorithm_discriminant = algorithm_to_u8(self.algorithm);
+    // `words` without stopwords, unstemmed, whether or not the stemmer drops
+    // them while stemming
+    fn filter_stopwords(&self, py: Python<'_>, words: Vec<String>) -> Vec<String> {
+        py.detach(|| {
+            words
+                .into_iter()
+                .filter(|word| !self.stopwords.matches(self.normalizer, word))
+                .collect()
+        })
+    }
 
+    // Counters of the cache this stemmer uses (all zero when caching is disabled)
+    fn cache_stats(&self) -> CacheStats {
+        self.shared_cache()
+            .map(|cache| cache.stats())
+            .unwrap_or_default()
+    }
+
+    #[inline(always)]
+    pub fn stem_words(&self, inputs: Vec<String>) -> Vec<String> {
+        let inputs = self.without_dropped(inputs);
+        let mut session = self.shared_cache().map(|cache| cache.session());
         inputs
             .iter()
-            .map(|word| {
-                let cache_key = (algorithm_discriminant, word.clone());
+            .map(|word| self.stem_input(session.as_mut(), word))
+            .collect()
+    }
+}
 
-                if let Some(entry) = cache.get(&cache_key) {
-                    return entry.value().clone();
-                }
+// Bound the shared cache by entry count and/or approximate bytes (None = unlimited).
+// Lowering a limit evicts least-recently-used entries right away.
+#[pyfunction]
+#[pyo3(signature = (max_entries = Some(DEFAULT_MAX_ENTRIES), max_bytes = None))]
+fn set_cache_limits(max_entries: Option<usize>, max_bytes: Option<usize>) {
+    default_cache().set_limits(max_entries, max_bytes);
+}
 
-                let result = Stemmer::create(self.algorithm).stem(word).into_owned();
-                cache.insert(cache_key, result.clone());
-                result
-            })
-            .collect()
+#[pyfunction]
+fn get_cache_limits() -> (Option<usize>, Option<usize>) {
+    default_cache().limits()
+}
+
+// Drop cached stems, either all of them or only those of one language
+#[pyfunction]
+#[pyo3(signature = (lang = None))]
+fn clear_cache(lang: Option<&str>) -> PyResult<()> {
+    match lang {
+        Some(lang) => default_cache().clear_language(algorithm_to_u8(parse_language(lang)?)),
+        None => default_cache().clear(),
     }
+    Ok(())
+}
+
+// Release memory left over by evicted or cleared entries
+#[pyfunction]
+fn shrink_cache() {
+    default_cache().shrink_to_fit();
+}
+
+// Write the shared cache to a binary file, returning the number of entries saved
+#[pyfunction]
+fn save_cache(py: Python<'_>, path: PathBuf) -> PyResult<usize> {
+    py.detach(|| persist::save(default_cache(), &path))
+}
+
+// Fill the shared cache from a file written by `save_cache`, returning the
+// number of entries read. Files from an incompatible build are rejected.
+#[pyfunction]
+fn load_cache(py: Python<'_>, path: PathBuf) -> PyResult<usize> {
+    py.detach(|| persist::load(default_cache(), &path))
+}
+
+#[pyfunction]
+fn cache_stats() -> CacheStats {
+    default_cache().stats()
+}
+
+// Languages this build can stem, in alphabetical order
+#[pyfunction]
+fn supported_languages() -> Vec<LanguageInfo> {
+    languages::LANGUAGES
+        .iter()
+        .map(LanguageInfo::from)
+        .collect()
 }
 
 #[pymodule]
 fn py_rust_stemmers_tuned(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<SnowballStemmer>()?;
+    m.add_class::<CacheStats>()?;
+    m.add_class::<PyStemCache>()?;
+    m.add_class::<CacheWarmup>()?;
+    m.add_class::<LanguageInfo>()?;
+    m.add_class::<LanguageDetection>()?;
+    m.add_class::<AutoStemmer>()?;
+    m.add_class::<MultiLanguageStemmer>()?;
+    m.add_class::<Tokenizer>()?;
+    m.add_class::<StemmedToken>()?;
+    m.add_class::<StemRecorder>()?;
+    m.add_class::<TokenPolicy>()?;
+    m.add_function(wrap_pyfunction!(set_cache_limits, m)?)?;
+    m.add_function(wrap_pyfunction!(get_cache_limits, m)?)?;
+    m.add_function(wrap_pyfunction!(cache_stats, m)?)?;
+    m.add_function(wrap_pyfunction!(clear_cache, m)?)?;
+    m.add_function(wrap_pyfunction!(shrink_cache, m)?)?;
+    m.add_function(wrap_pyfunction!(save_cache, m)?)?;
+    m.add_function(wrap_pyfunction!(load_cache, m)?)?;
+    m.add_function(wrap_pyfunction!(supported_languages, m)?)?;
+    m.add_function(wrap_pyfunction!(detect::detect_language, m)?)?;
     Ok(())
 }
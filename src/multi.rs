@@ -0,0 +1,236 @@
+// Per-token routing for mixed-script text: each token is stemmed with the
+// algorithm configured for its script, so Latin product names inside Russian
+// text get an English (or other Latin-script) stemmer instead of a Russian one.
+
+use crate::admission::{Admission, AdmissionArg};
+use crate::algorithm_to_u8;
+use crate::algorithms::{Algorithm, Stemmer};
+use crate::cache::{self, CacheSession, StemCache};
+use crate::languages::{self, script_of, LANGUAGES};
+use crate::namespace::{CacheArg, PyStemCache};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+struct Route {
+    script: &'static str,
+    algorithm: Algorithm,
+    // Cache key of the algorithm, so routed stems share the cache with
+    // `SnowballStemmer`s of the same language
+    discriminant: u8,
+    stemmer: Stemmer,
+}
+
+impl Route {
+    fn new(script: &'static str, algorithm: Algorithm) -> Self {
+        Route {
+            script,
+            algorithm,
+            discriminant: algorithm_to_u8(algorithm),
+            stemmer: Stemmer::create(algorithm),
+        }
+    }
+
+    #[inline(always)]
+    fn stem(
+        &self,
+        session: Option<&mut CacheSession<'_>>,
+        word: &str,
+        admission: Admission,
+    ) -> String {
+        cache::stem_through(session, self.discriminant, &self.stemmer, word, admission)
+    }
+}
+
+// The script names used in `LanguageInfo.script`, matched case-insensitively
+fn parse_script(name: &str) -> PyResult<&'static str> {
+    LANGUAGES
+        .iter()
+        .map(|language| language.script)
+        .find(|script| script.eq_ignore_ascii_case(name.trim()))
+        .ok_or_else(|| PyValueError::new_err(format!("Unsupported script: {}", name)))
+}
+
+// Scripts only one supported language is written in route to it by default
+fn default_route(script: &'static str) -> Option<Algorithm> {
+    let mut languages = LANGUAGES
+        .iter()
+        .filter(|language| language.script == script);
+    match (languages.next(), languages.next()) {
+        (Some(language), None) => Some(language.algorithm),
+        _ => None,
+    }
+}
+
+#[pyclass(module = "py_rust_stemmers_tuned", frozen)]
+pub struct MultiLanguageStemmer {
+    primary: Algorithm,
+    // The primary language's route comes first
+    routes: Vec<Route>,
+    cache: Option<Py<PyStemCache>>,
+    admission: Admission,
+}
+
+impl MultiLanguageStemmer {
+    #[inline(always)]
+    fn shared_cache(&self) -> Option<&Arc<StemCache>> {
+        self.cache.as_ref().map(|cache| cache.get().shared())
+    }
+
+    #[inline(always)]
+    fn route(&self, word: &str) -> Option<&Route> {
+        let script = script_of(word)?;
+        self.routes.iter().find(|route| route.script == script)
+    }
+
+    // Tokens without a route (numbers, unsupported scripts) are returned as is
+    #[inline(always)]
+    fn stem_token(&self, session: Option<&mut CacheSession<'_>>, word: &str) -> String {
+        match self.route(word) {
+            Some(route) => route.stem(session, word, self.admission),
+            None => word.to_owned(),
+        }
+    }
+}
+
+#[pymethods]
+impl MultiLanguageStemmer {
+    #[new]
+    #[pyo3(signature = (
+        primary,
+        fallbacks = None,
+        cache = CacheArg::Enabled(true),
+        admission = AdmissionArg::Sightings(1),
+    ))]
+    fn new(
+        py: Python<'_>,
+        primary: &str,
+        fallbacks: Option<HashMap<String, Option<String>>>,
+        cache: CacheArg,
+        admission: AdmissionArg,
+    ) -> PyResult<Self> {
+        let primary_language = languages::resolve(primary)?;
+        let mut routes = vec![Route::new(
+            primary_language.script,
+            primary_language.algorithm,
+        )];
+
+        let mut overrides = HashMap::new();
+        for (script, language) in fallbacks.unwrap_or_default() {
+            let script = parse_script(&script)?;
+            if script == primary_language.script {
+                return Err(PyValueError::new_err(format!(
+                    "{} tokens already go to the primary language {}",
+                    script, primary_language.name
+                )));
+            }
+            let algorithm = match language {
+                Some(language) => {
+                    let language = languages::resolve(&language)?;
+                    if language.script != script {
+                        return Err(PyValueError::new_err(format!(
+                            "{} is not written in {} script",
+                            language.name, script
+                        )));
+                    }
+                    Some(language.algorithm)
+                }
+                // `None` leaves the script's tokens unstemmed
+                None => None,
+            };
+            overrides.insert(script, algorithm);
+        }
+
+        let mut scripts: Vec<&'static str> = LANGUAGES.iter().map(|l| l.script).collect();
+        scripts.sort_unstable();
+        scripts.dedup();
+        for script in scripts {
+            if script == primary_language.script {
+                continue;
+            }
+            let algorithm = match overrides.get(script) {
+                Some(algorithm) => *algorithm,
+                None => default_route(script),
+            };
+            if let Some(algorithm) = algorithm {
+                routes.push(Route::new(script, algorithm));
+            }
+        }
+
+        Ok(MultiLanguageStemmer {
+            primary: primary_language.algorithm,
+            routes,
+            cache: cache.resolve(py)?,
+            admission: admission.resolve()?,
+        })
+    }
+
+    #[getter]
+    fn primary(&self) -> &'static str {
+        languages::language_of(self.primary).name
+    }
+
+    // Script name -> language its tokens are stemmed with
+    #[getter]
+    fn routes(&self) -> HashMap<&'static str, &'static str> {
+        self.routes
+            .iter()
+            .map(|route| (route.script, languages::language_of(route.algorithm).name))
+            .collect()
+    }
+
+    #[getter]
+    fn cache(&self, py: Python<'_>) -> Option<Py<PyStemCache>> {
+        self.cache.as_ref().map(|cache| cache.clone_ref(py))
+    }
+
+    // The language a token would be stemmed with, or None if it is left as is
+    fn language_for(&self, word: &str) -> Option<&'static str> {
+        self.route(word)
+            .map(|route| languages::language_of(route.algorithm).name)
+    }
+
+    fn stem_word(&self, word: &str) -> String {
+        match self.shared_cache() {
+            Some(cache) => self.stem_token(Some(&mut cache.session()), word),
+            None => self.stem_token(None, word),
+        }
+    }
+
+    fn stem_words(&self, words: Vec<String>) -> Vec<String> {
+        let Some(cache) = self.shared_cache() else {
+            return words
+                .iter()
+                .map(|word| self.stem_token(None, word))
+                .collect();
+        };
+        let mut session = cache.session();
+        words
+            .iter()
+            .map(|word| self.stem_token(Some(&mut session), word))
+            .collect()
+    }
+
+    fn stem_words_parallel(&self, py: Python<'_>, words: Vec<String>) -> PyResult<Vec<String>> {
+        let cache = self.shared_cache();
+        Ok(py.detach(|| {
+            let Some(cache) = cache else {
+                return words
+                    .par_iter()
+                    .with_min_len(500)
+                    .map(|word| self.stem_token(None, word))
+                    .collect();
+            };
+            words
+                .par_iter()
+                .with_min_len(250)
+                .map_init(
+                    || cache.session(),
+                    |session, word| self.stem_token(Some(session), word),
+                )
+                .collect()
+        }))
+    }
+}
@@ -0,0 +1,214 @@
+// Python-facing cache objects. Stemmers that are given the same `StemCache`
+// (or the same namespace name) share entries, limits and stats; different
+// objects are fully independent maps.
+
+use crate::cache::{
+    default_shard_count, CacheStats, StemCache, DEFAULT_L1_CAPACITY, DEFAULT_MAX_ENTRIES,
+};
+use crate::{algorithm_to_u8, parse_language, persist};
+use pyo3::prelude::*;
+use pyo3::types::PyTuple;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+
+pub const DEFAULT_NAMESPACE: &str = "default";
+
+static DEFAULT_CACHE: OnceLock<Arc<StemCache>> = OnceLock::new();
+static NAMED_CACHES: OnceLock<Mutex<HashMap<String, Arc<StemCache>>>> = OnceLock::new();
+
+// Process-wide cache used by `cache=True` and the module-level cache functions
+pub fn default_cache() -> &'static Arc<StemCache> {
+    DEFAULT_CACHE.get_or_init(Arc::default)
+}
+
+fn named_cache(name: &str) -> Arc<StemCache> {
+    if name == DEFAULT_NAMESPACE {
+        return default_cache().clone();
+    }
+    NAMED_CACHES
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .entry(name.to_owned())
+        .or_default()
+        .clone()
+}
+
+#[pyclass(name = "StemCache", module = "py_rust_stemmers_tuned", frozen)]
+pub struct PyStemCache {
+    cache: Arc<StemCache>,
+    // Set for namespaces, `None` for anonymous caches
+    name: Option<String>,
+}
+
+impl PyStemCache {
+    pub fn default_namespace() -> Self {
+        PyStemCache {
+            cache: default_cache().clone(),
+            name: Some(DEFAULT_NAMESPACE.to_owned()),
+        }
+    }
+
+    pub fn namespace(name: &str) -> Self {
+        PyStemCache {
+            cache: named_cache(name),
+            name: Some(name.to_owned()),
+        }
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    #[inline(always)]
+    pub fn shared(&self) -> &Arc<StemCache> {
+        &self.cache
+    }
+}
+
+#[pymethods]
+impl PyStemCache {
+    // A new cache that is not shared with anything until passed to a stemmer.
+    // `shards` defaults to a count scaled to the number of CPUs.
+    #[new]
+    #[pyo3(signature = (
+        max_entries = Some(DEFAULT_MAX_ENTRIES),
+        max_bytes = None,
+        shards = None,
+        l1_capacity = DEFAULT_L1_CAPACITY,
+    ))]
+    fn new(
+        max_entries: Option<usize>,
+        max_bytes: Option<usize>,
+        shards: Option<usize>,
+        l1_capacity: usize,
+    ) -> Self {
+        let shards = shards.unwrap_or_else(default_shard_count);
+        PyStemCache {
+            cache: Arc::new(StemCache::new(max_entries, max_bytes, shards, l1_capacity)),
+            name: None,
+        }
+    }
+
+    // The cache registered under `name`, created with default limits on first use
+    #[staticmethod]
+    fn named(name: &str) -> Self {
+        PyStemCache::namespace(name)
+    }
+
+    #[staticmethod]
+    fn default() -> Self {
+        PyStemCache::default_namespace()
+    }
+
+    #[getter(name)]
+    fn py_name(&self) -> Option<&str> {
+        self.name()
+    }
+
+    #[pyo3(signature = (max_entries = Some(DEFAULT_MAX_ENTRIES), max_bytes = None))]
+    fn set_limits(&self, max_entries: Option<usize>, max_bytes: Option<usize>) {
+        self.cache.set_limits(max_entries, max_bytes);
+    }
+
+    fn get_limits(&self) -> (Option<usize>, Option<usize>) {
+        self.cache.limits()
+    }
+
+    #[getter]
+    fn shards(&self) -> usize {
+        self.cache.shard_count()
+    }
+
+    // Entries kept per thread in front of the shared shards, 0 disables it
+    fn set_l1_capacity(&self, capacity: usize) {
+        self.cache.set_l1_capacity(capacity);
+    }
+
+    fn get_l1_capacity(&self) -> usize {
+        self.cache.l1_capacity()
+    }
+
+    fn stats(&self) -> CacheStats {
+        self.cache.stats()
+    }
+
+    #[pyo3(signature = (lang = None))]
+    fn clear(&self, lang: Option<&str>) -> PyResult<()> {
+        match lang {
+            Some(lang) => self
+                .cache
+                .clear_language(algorithm_to_u8(parse_language(lang)?)),
+            None => self.cache.clear(),
+        }
+        Ok(())
+    }
+
+    fn shrink(&self) {
+        self.cache.shrink_to_fit();
+    }
+
+    fn save(&self, py: Python<'_>, path: PathBuf) -> PyResult<usize> {
+        py.detach(|| persist::save(&self.cache, &path))
+    }
+
+    fn load(&self, py: Python<'_>, path: PathBuf) -> PyResult<usize> {
+        py.detach(|| persist::load(&self.cache, &path))
+    }
+
+    fn __eq__(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.cache, &other.cache)
+    }
+
+    fn __hash__(&self) -> u64 {
+        Arc::as_ptr(&self.cache) as usize as u64
+    }
+
+    // Namespaces pickle by name, so they attach to the namespace of the same
+    // name in the unpickling process. Other caches come back empty, with the
+    // same limits.
+    fn __reduce__<'py>(
+        &self,
+        py: Python<'py>,
+    ) -> PyResult<(Bound<'py, PyAny>, Bound<'py, PyTuple>)> {
+        let class = py.get_type::<PyStemCache>();
+        if let Some(name) = &self.name {
+            return Ok((class.getattr("named")?, (name,).into_pyobject(py)?));
+        }
+        let (max_entries, max_bytes) = self.cache.limits();
+        let args = (
+            max_entries,
+            max_bytes,
+            self.cache.shard_count(),
+            self.cache.l1_capacity(),
+        );
+        Ok((class.into_any(), args.into_pyobject(py)?))
+    }
+
+    fn __repr__(&self) -> String {
+        match &self.name {
+            Some(name) => format!("StemCache.named({:?})", name),
+            None => format!("<StemCache at {:p}>", Arc::as_ptr(&self.cache)),
+        }
+    }
+}
+
+// What the `cache` argument of `SnowballStemmer` accepts
+#[derive(FromPyObject)]
+pub enum CacheArg {
+    Enabled(bool),
+    Cache(Py<PyStemCache>),
+    Namespace(String),
+}
+
+impl CacheArg {
+    pub fn resolve(self, py: Python<'_>) -> PyResult<Option<Py<PyStemCache>>> {
+        Ok(match self {
+            CacheArg::Enabled(false) => None,
+            CacheArg::Enabled(true) => Some(Py::new(py, PyStemCache::default_namespace())?),
+            CacheArg::Cache(cache) => Some(cache),
+            CacheArg::Namespace(name) => Some(Py::new(py, PyStemCache::namespace(&name))?),
+        })
+    }
+}
@@ -0,0 +1,274 @@
+// Input normalization applied before the cache lookup, so inputs that differ
+// only in case or Unicode composition ("Café", "café", "cafe\u{301}") share
+// one cache entry and one stem.
+
+use crate::algorithms::Algorithm;
+use pyo3::exceptions::PyValueError;
+use pyo3::PyResult;
+use std::borrow::Cow;
+use unicode_normalization::{is_nfc_quick, is_nfkc_quick, IsNormalized, UnicodeNormalization};
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Case {
+    Preserve,
+    Lower,
+    // Full Unicode case folding (ß -> ss, ﬁ -> fi, final σ -> σ)
+    Fold,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Form {
+    Nfc,
+    Nfkc,
+}
+
+// Spelling rules of one language, for `orthography=True`. Generic lowercasing
+// gets Turkish I wrong, and the other languages have variant spellings that
+// their stemmers would otherwise cache (or stem) separately.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Orthography {
+    // I lowercases to dotless ı, and İ to i
+    Turkish,
+    // Accents (tonos, dialytika) dropped, final ς written σ
+    Greek,
+    // ß written ss; ae, oe and ue written ä, ö and ü, as in German2
+    German,
+    // Vowel marks (tashkeel) and tatweel dropped
+    Arabic,
+    // The ĳ ligature written ij
+    Dutch,
+    // Languages without rules of their own
+    Generic,
+}
+
+impl Orthography {
+    pub fn for_algorithm(algorithm: Algorithm) -> Self {
+        match algorithm {
+            Algorithm::Turkish => Orthography::Turkish,
+            Algorithm::Greek => Orthography::Greek,
+            Algorithm::German => Orthography::German,
+            Algorithm::Arabic => Orthography::Arabic,
+            Algorithm::Dutch => Orthography::Dutch,
+            _ => Orthography::Generic,
+        }
+    }
+
+    // Whether the rules can change an ASCII word beyond lowercasing it
+    fn touches_ascii(self) -> bool {
+        matches!(self, Orthography::Turkish | Orthography::German)
+    }
+
+    fn lowercase(self, text: &str) -> String {
+        if self != Orthography::Turkish {
+            return text.to_lowercase();
+        }
+        let mut lowered = String::with_capacity(text.len());
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                // A decomposed İ is I followed by a combining dot above
+                'I' if chars.next_if_eq(&'\u{307}').is_some() => lowered.push('i'),
+                'I' => lowered.push('ı'),
+                'İ' => lowered.push('i'),
+                _ => lowered.extend(c.to_lowercase()),
+            }
+        }
+        lowered
+    }
+
+    fn fold(self, text: &str) -> String {
+        // Full folding maps İ to i plus a combining dot, and I to i
+        if self == Orthography::Turkish && text.contains(['I', 'İ']) {
+            return caseless::default_case_fold_str(&self.lowercase(text));
+        }
+        caseless::default_case_fold_str(text)
+    }
+
+    fn respell<'a>(self, text: Cow<'a, str>) -> Cow<'a, str> {
+        match self {
+            Orthography::Greek if text.chars().any(is_greek_variant) => Cow::Owned(
+                text.nfd()
+                    .filter(|c| !('\u{300}'..='\u{36f}').contains(c))
+                    .map(|c| if c == 'ς' { 'σ' } else { c })
+                    .collect(),
+            ),
+            Orthography::German if text.contains(['ß', 'ẞ']) || has_german_digraph(&text) => {
+                Cow::Owned(respell_german(&text))
+            }
+            Orthography::Arabic if text.chars().any(is_arabic_mark) => {
+                Cow::Owned(text.chars().filter(|&c| !is_arabic_mark(c)).collect())
+            }
+            Orthography::Dutch if text.contains(['ĳ', 'Ĳ']) => {
+                Cow::Owned(text.replace('ĳ', "ij").replace('Ĳ', "IJ"))
+            }
+            _ => text,
+        }
+    }
+}
+
+fn is_greek_variant(c: char) -> bool {
+    let mut decomposed = 0;
+    unicode_normalization::char::decompose_canonical(c, |_| decomposed += 1);
+    c == 'ς' || decomposed > 1 || ('\u{300}'..='\u{36f}').contains(&c)
+}
+
+fn is_arabic_mark(c: char) -> bool {
+    // Fathatan to sukun, maddah and hamza above and below and the other
+    // combining marks up to wavy hamza, superscript alef, and tatweel
+    // (kashida)
+    ('\u{64b}'..='\u{65f}').contains(&c) || c == '\u{670}' || c == '\u{640}'
+}
+
+fn has_german_digraph(text: &str) -> bool {
+    text.as_bytes()
+        .windows(2)
+        .any(|pair| matches!(pair, [b'a' | b'o' | b'u', b'e']))
+}
+
+// The German2 Snowball variant's spellings; the stemmer then treats
+// "Mueller" and "Müller" alike
+fn respell_german(text: &str) -> String {
+    let mut respelled = String::with_capacity(text.len());
+    let mut previous = None;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        let umlaut = match c {
+            'a' => Some('ä'),
+            'o' => Some('ö'),
+            // German2 leaves "qu" alone and treats u between vowels as a consonant
+            'u' if !previous.is_some_and(|p| "aeiouyäöüq".contains(p)) => Some('ü'),
+            _ => None,
+        };
+        match umlaut {
+            Some(umlaut) if chars.next_if_eq(&'e').is_some() => respelled.push(umlaut),
+            _ => match c {
+                'ß' | 'ẞ' => respelled.push_str("ss"),
+                _ => respelled.push(c),
+            },
+        }
+        previous = Some(c);
+    }
+    respelled
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Normalizer {
+    pub case: Case,
+    pub form: Option<Form>,
+    pub orthography: Option<Orthography>,
+}
+
+impl Default for Normalizer {
+    fn default() -> Self {
+        Normalizer {
+            case: Case::Preserve,
+            form: None,
+            orthography: None,
+        }
+    }
+}
+
+impl Normalizer {
+    pub fn parse(case: Option<&str>, form: Option<&str>) -> PyResult<Self> {
+        let case = match case.map(str::to_lowercase).as_deref() {
+            None => Case::Preserve,
+            Some("lower") => Case::Lower,
+            Some("fold" | "casefold") => Case::Fold,
+            Some(_) => {
+                return Err(PyValueError::new_err(format!(
+                    "Unsupported case: {} (expected 'lower' or 'fold')",
+                    case.unwrap_or_default()
+                )))
+            }
+        };
+        let form = match form.map(str::to_uppercase).as_deref() {
+            None => None,
+            Some("NFC") => Some(Form::Nfc),
+            Some("NFKC") => Some(Form::Nfkc),
+            Some(_) => {
+                return Err(PyValueError::new_err(format!(
+                    "Unsupported normalization form: {} (expected 'NFC' or 'NFKC')",
+                    form.unwrap_or_default()
+                )))
+            }
+        };
+        Ok(Normalizer {
+            case,
+            form,
+            orthography: None,
+        })
+    }
+
+    // Also apply the spelling rules of `algorithm` when `enabled`
+    pub fn with_orthography(self, algorithm: Algorithm, enabled: bool) -> Self {
+        Normalizer {
+            orthography: enabled.then(|| Orthography::for_algorithm(algorithm)),
+            ..self
+        }
+    }
+
+    // The values `parse` accepts for this normalizer, for pickling and repr
+    pub fn case_name(self) -> Option<&'static str> {
+        match self.case {
+            Case::Preserve => None,
+            Case::Lower => Some("lower"),
+            Case::Fold => Some("fold"),
+        }
+    }
+
+    pub fn form_name(self) -> Option<&'static str> {
+        self.form.map(|form| match form {
+            Form::Nfc => "NFC",
+            Form::Nfkc => "NFKC",
+        })
+    }
+
+    #[inline(always)]
+    pub fn apply<'a>(self, word: &'a str) -> Cow<'a, str> {
+        if self.case == Case::Preserve && self.form.is_none() && self.orthography.is_none() {
+            return Cow::Borrowed(word);
+        }
+        let orthography = self.orthography.unwrap_or(Orthography::Generic);
+        // ASCII is already in every normalization form, and folds like it lowercases
+        if word.is_ascii() && !orthography.touches_ascii() {
+            if self.case != Case::Preserve && word.bytes().any(|b| b.is_ascii_uppercase()) {
+                return Cow::Owned(word.to_ascii_lowercase());
+            }
+            return Cow::Borrowed(word);
+        }
+        let composed = self.compose(Cow::Borrowed(word));
+        let cased: Cow<'a, str> = match self.case {
+            Case::Preserve => composed,
+            Case::Lower if composed.chars().any(char::is_uppercase) => {
+                Cow::Owned(orthography.lowercase(&composed))
+            }
+            Case::Lower => composed,
+            Case::Fold => {
+                let folded = orthography.fold(&composed);
+                if folded == *composed {
+                    composed
+                } else {
+                    Cow::Owned(folded)
+                }
+            }
+        };
+        let respelled = orthography.respell(cased);
+        if matches!(respelled, Cow::Borrowed(_)) {
+            return respelled;
+        }
+        // Case mappings and respelling can leave text that is no longer composed
+        self.compose(respelled)
+    }
+
+    fn compose(self, text: Cow<'_, str>) -> Cow<'_, str> {
+        match self.form {
+            Some(Form::Nfc) if is_nfc_quick(text.chars()) != IsNormalized::Yes => {
+                Cow::Owned(text.nfc().collect())
+            }
+            Some(Form::Nfkc) if is_nfkc_quick(text.chars()) != IsNormalized::Yes => {
+                Cow::Owned(text.nfkc().collect())
+            }
+            _ => text,
+        }
+    }
+}
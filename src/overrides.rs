@@ -0,0 +1,154 @@
+// Per-stemmer exceptions to the algorithm: protected words that are returned
+// unstemmed, and an explicit word -> stem map, e.g. to keep "news" from
+// becoming "new". Both are checked before the cache, so they never reach a
+// cache shared with other stemmers.
+
+use crate::normalize::Normalizer;
+use crate::stopwords::extract_words;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+// `protected`: a file with one word per line (or a JSON list), or an
+// iterable of words
+#[derive(FromPyObject)]
+pub enum ProtectedArg<'py> {
+    Path(PathBuf),
+    Words(Bound<'py, PyAny>),
+}
+
+// `overrides`: a TSV file of word<TAB>stem lines (or a JSON object), or a dict
+#[derive(FromPyObject)]
+pub enum OverridesArg {
+    Path(PathBuf),
+    Stems(HashMap<String, String>),
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("json"))
+}
+
+// Parsed with Python's json module rather than another dependency
+fn read_json<'py>(py: Python<'py>, path: &Path) -> PyResult<Bound<'py, PyAny>> {
+    let text = std::fs::read_to_string(path)?;
+    py.import("json")?.call_method1("loads", (text,))
+}
+
+// Non-empty lines that are not '#' comments, with their line numbers
+fn lines(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.trim_end_matches('\r')))
+        .filter(|(_, line)| !line.trim().is_empty() && !line.starts_with('#'))
+}
+
+fn load_protected(py: Python<'_>, arg: ProtectedArg<'_>) -> PyResult<HashSet<String>> {
+    match arg {
+        ProtectedArg::Words(words) => extract_words(&words),
+        ProtectedArg::Path(path) if is_json(&path) => extract_words(&read_json(py, &path)?),
+        ProtectedArg::Path(path) => {
+            let text = std::fs::read_to_string(&path)?;
+            Ok(lines(&text)
+                .map(|(_, line)| line.trim().to_owned())
+                .collect())
+        }
+    }
+}
+
+fn load_overrides(py: Python<'_>, arg: OverridesArg) -> PyResult<HashMap<String, String>> {
+    let path = match arg {
+        OverridesArg::Stems(stems) => return Ok(stems),
+        OverridesArg::Path(path) if is_json(&path) => return read_json(py, &path)?.extract(),
+        OverridesArg::Path(path) => path,
+    };
+    let text = std::fs::read_to_string(&path)?;
+    lines(&text)
+        .map(|(number, line)| match line.split_once('\t') {
+            Some((word, stem)) if !word.is_empty() && !stem.contains('\t') => {
+                Ok((word.to_owned(), stem.to_owned()))
+            }
+            _ => Err(PyValueError::new_err(format!(
+                "{}: line {}: expected word<TAB>stem",
+                path.display(),
+                number
+            ))),
+        })
+        .collect()
+}
+
+#[derive(Clone, Default, PartialEq)]
+pub struct Overrides {
+    // Keys are normalized like the stemmer's input, so they match after
+    // case folding and the other normalization options
+    protected: Option<Arc<HashSet<String>>>,
+    stems: Option<Arc<HashMap<String, String>>>,
+}
+
+impl Overrides {
+    pub fn new(
+        py: Python<'_>,
+        normalizer: Normalizer,
+        protected: Option<ProtectedArg<'_>>,
+        stems: Option<OverridesArg>,
+    ) -> PyResult<Self> {
+        let protected = protected
+            .map(|arg| load_protected(py, arg))
+            .transpose()?
+            .map(|words| {
+                let words = words
+                    .iter()
+                    .map(|word| normalizer.apply(word).into_owned())
+                    .collect();
+                Arc::new(words)
+            });
+        let stems = stems
+            .map(|arg| load_overrides(py, arg))
+            .transpose()?
+            .map(|stems| {
+                let stems = stems
+                    .into_iter()
+                    .map(|(word, stem)| (normalizer.apply(&word).into_owned(), stem))
+                    .collect();
+                Arc::new(stems)
+            });
+        Ok(Overrides { protected, stems })
+    }
+
+    // The stem of a normalized `word`, if it is overridden or protected. An
+    // override wins over protection.
+    #[inline(always)]
+    pub fn get<'a>(&'a self, word: &'a str) -> Option<&'a str> {
+        if let Some(stem) = self.stems.as_ref().and_then(|stems| stems.get(word)) {
+            return Some(stem);
+        }
+        self.protected
+            .as_ref()
+            .is_some_and(|protected| protected.contains(word))
+            .then_some(word)
+    }
+
+    // The constructor arguments that recreate these overrides, protected
+    // words sorted so they pickle the same way every time
+    pub fn protected(&self) -> Option<Vec<String>> {
+        self.protected.as_ref().map(|protected| {
+            let mut words: Vec<String> = protected.iter().cloned().collect();
+            words.sort_unstable();
+            words
+        })
+    }
+
+    pub fn stems(&self) -> Option<HashMap<String, String>> {
+        self.stems.as_deref().cloned()
+    }
+
+    pub fn protected_len(&self) -> Option<usize> {
+        self.protected.as_ref().map(|protected| protected.len())
+    }
+
+    pub fn stems_len(&self) -> Option<usize> {
+        self.stems.as_ref().map(|stems| stems.len())
+    }
+}
@@ -0,0 +1,187 @@
+// Binary snapshot of a stem cache, so restarted workers don't start cold.
+//
+// Layout (integers are little-endian, lengths are LEB128 varints):
+//   magic "PRSC" | format version u16 | rust-stemmers version (len + bytes)
+//   | language count u8 + languages: discriminant u8, fingerprint u64
+//   | entry count varint
+//   | entries: discriminant u8, word (len + bytes), stem (len + bytes)
+//
+// A file is only accepted if the format and rust-stemmers versions match
+// this build, and so do the fingerprints of the algorithms implemented in
+// this crate (0 for the others), since a different version of an algorithm
+// may produce different stems.
+
+use crate::admission::Admission;
+use crate::algorithm_from_u8;
+use crate::algorithms::fingerprint;
+use crate::cache::StemCache;
+use crate::languages::language_of;
+use pyo3::exceptions::PyValueError;
+use pyo3::PyResult;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+const MAGIC: &[u8; 4] = b"PRSC";
+const FORMAT_VERSION: u16 = 2;
+// Read from Cargo.lock by build.rs
+const RUST_STEMMERS_VERSION: &str = env!("RUST_STEMMERS_VERSION");
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn write_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    write_varint(out, bytes.len() as u64);
+    out.extend_from_slice(bytes);
+}
+
+fn invalid(message: &str) -> pyo3::PyErr {
+    PyValueError::new_err(format!("Invalid stem cache file: {}", message))
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> PyResult<&'a [u8]> {
+        if len > self.data.len() {
+            return Err(invalid("unexpected end of file"));
+        }
+        let (head, tail) = self.data.split_at(len);
+        self.data = tail;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> PyResult<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u64(&mut self) -> PyResult<u64> {
+        let bytes = self.take(8)?;
+        Ok(u64::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn varint(&mut self) -> PyResult<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.u8()?;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(invalid("malformed length"))
+    }
+
+    fn str(&mut self) -> PyResult<&'a str> {
+        let len = self.varint()? as usize;
+        std::str::from_utf8(self.take(len)?).map_err(|_| invalid("malformed string"))
+    }
+}
+
+// Write all entries of `cache` to `path`, returning how many were written.
+// Stems of programs loaded at runtime are left out, as their discriminants
+// only mean something in this process. The file is written next to `path`
+// first and then renamed over it, so a crash mid-write never leaves a
+// truncated snapshot behind.
+pub fn save(cache: &StemCache, path: &Path) -> PyResult<usize> {
+    let mut body = Vec::new();
+    let mut languages = BTreeSet::new();
+    let mut count = 0usize;
+    cache.for_each_entry(|(lang, word), stem| {
+        if algorithm_from_u8(*lang).is_none() {
+            return;
+        }
+        body.push(*lang);
+        write_bytes(&mut body, word.as_bytes());
+        write_bytes(&mut body, stem.as_bytes());
+        languages.insert(*lang);
+        count += 1;
+    });
+
+    let mut out = Vec::with_capacity(body.len() + 64);
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+    write_bytes(&mut out, RUST_STEMMERS_VERSION.as_bytes());
+    out.push(languages.len() as u8);
+    for lang in languages {
+        let algorithm = algorithm_from_u8(lang).expect("only built-in languages are saved");
+        out.push(lang);
+        out.extend_from_slice(&fingerprint(algorithm).unwrap_or(0).to_le_bytes());
+    }
+    write_varint(&mut out, count as u64);
+    out.extend_from_slice(&body);
+
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    fs::write(&tmp, &out)?;
+    fs::rename(&tmp, path)?;
+    Ok(count)
+}
+
+// Insert the entries stored at `path` into `cache`, returning how many were
+// read. The whole file is validated before anything is inserted. Cache limits
+// still apply, so a small cache keeps only the most recently used part of a
+// large snapshot.
+pub fn load(cache: &StemCache, path: &Path) -> PyResult<usize> {
+    let data = fs::read(path)?;
+    let mut reader = Reader { data: &data };
+
+    if reader.take(MAGIC.len()).ok() != Some(MAGIC.as_slice()) {
+        return Err(invalid("not a stem cache file"));
+    }
+    let version = u16::from_le_bytes([reader.u8()?, reader.u8()?]);
+    if version != FORMAT_VERSION {
+        return Err(invalid(&format!(
+            "format version {} is not supported (expected {})",
+            version, FORMAT_VERSION
+        )));
+    }
+    let stemmers_version = reader.str()?;
+    if stemmers_version != RUST_STEMMERS_VERSION {
+        return Err(invalid(&format!(
+            "written with rust-stemmers {} (this build uses {})",
+            stemmers_version, RUST_STEMMERS_VERSION
+        )));
+    }
+    let language_count = reader.u8()? as usize;
+    let mut languages = Vec::with_capacity(language_count);
+    for _ in 0..language_count {
+        let lang = reader.u8()?;
+        let algorithm = algorithm_from_u8(lang)
+            .ok_or_else(|| invalid(&format!("unknown language id {}", lang)))?;
+        if reader.u64()? != fingerprint(algorithm).unwrap_or(0) {
+            return Err(invalid(&format!(
+                "written with a different version of the {} algorithm",
+                language_of(algorithm).name
+            )));
+        }
+        languages.push(lang);
+    }
+
+    let count = reader.varint()? as usize;
+    let mut entries = Vec::with_capacity(count.min(reader.data.len()));
+    for _ in 0..count {
+        let lang = reader.u8()?;
+        if !languages.contains(&lang) {
+            return Err(invalid(&format!("undeclared language id {}", lang)));
+        }
+        let word = reader.str()?;
+        let stem = reader.str()?;
+        entries.push((lang, word, stem));
+    }
+    if !reader.data.is_empty() {
+        return Err(invalid("trailing data"));
+    }
+
+    for (lang, word, stem) in entries {
+        cache.insert(lang, word, stem.to_owned(), Admission::Always);
+    }
+    Ok(count)
+}
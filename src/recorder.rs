@@ -0,0 +1,161 @@
+// Opt-in record of the surface forms that produced each stem, for query
+// suggestions and facet labels: "comput" came from "computer", "computing"
+// and "computation". Sharded by stem like the cache, so parallel stemming
+// rarely waits on a lock.
+
+use crate::cache::default_shard_count;
+use foldhash::fast::RandomState;
+use pyo3::prelude::*;
+use pyo3::types::PyTuple;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::hash::BuildHasher;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+// surface form -> times seen
+type Forms = HashMap<String, u64>;
+
+#[pyclass(module = "py_rust_stemmers_tuned", frozen)]
+pub struct StemRecorder {
+    shards: Box<[Mutex<HashMap<String, Forms>>]>,
+    hasher: RandomState,
+}
+
+impl StemRecorder {
+    fn shard(&self, stem: &str) -> MutexGuard<'_, HashMap<String, Forms>> {
+        let hash = self.hasher.hash_one(stem);
+        self.shards[hash as usize % self.shards.len()]
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    #[inline(always)]
+    pub fn record(&self, form: &str, stem: &str) {
+        self.add(form, stem, 1);
+    }
+
+    // Allocates only for stems and forms not seen before
+    fn add(&self, form: &str, stem: &str, count: u64) {
+        let mut shard = self.shard(stem);
+        let Some(forms) = shard.get_mut(stem) else {
+            shard.insert(stem.to_owned(), Forms::from([(form.to_owned(), count)]));
+            return;
+        };
+        match forms.get_mut(form) {
+            Some(seen) => *seen += count,
+            None => {
+                forms.insert(form.to_owned(), count);
+            }
+        }
+    }
+
+    fn for_each_stem(&self, mut f: impl FnMut(&str, &Forms)) {
+        for shard in self.shards.iter() {
+            let shard = shard.lock().unwrap_or_else(PoisonError::into_inner);
+            for (stem, forms) in shard.iter() {
+                f(stem, forms);
+            }
+        }
+    }
+}
+
+// The most frequent form, ties going to the alphabetically first
+fn display_form(forms: &Forms) -> Option<&str> {
+    forms
+        .iter()
+        .max_by(|a, b| a.1.cmp(b.1).then_with(|| b.0.cmp(a.0)))
+        .map(|(form, _)| form.as_str())
+}
+
+#[pymethods]
+impl StemRecorder {
+    // `counts` restores data from `export`: {stem: {surface form: count}}
+    #[new]
+    #[pyo3(signature = (counts = None))]
+    fn new(counts: Option<HashMap<String, Forms>>) -> Self {
+        let hasher = RandomState::default();
+        let shards = (0..default_shard_count())
+            .map(|_| Mutex::new(HashMap::new()))
+            .collect();
+        let recorder = StemRecorder { shards, hasher };
+        for (stem, forms) in counts.into_iter().flatten() {
+            for (form, count) in forms {
+                recorder.add(&form, &stem, count);
+            }
+        }
+        recorder
+    }
+
+    // The forms recorded for `stem` with their counts, most frequent first
+    fn forms(&self, stem: &str) -> Vec<(String, u64)> {
+        let shard = self.shard(stem);
+        let mut forms: Vec<(String, u64)> = shard
+            .get(stem)
+            .into_iter()
+            .flatten()
+            .map(|(form, &count)| (form.clone(), count))
+            .collect();
+        forms.sort_unstable_by(|a, b| Reverse(a.1).cmp(&Reverse(b.1)).then_with(|| a.0.cmp(&b.0)));
+        forms
+    }
+
+    // The most frequent form of `stem`, `None` if it was never recorded
+    fn display_form(&self, stem: &str) -> Option<String> {
+        self.shard(stem)
+            .get(stem)
+            .and_then(display_form)
+            .map(str::to_owned)
+    }
+
+    // `display_form` of every recorded stem
+    fn display_forms(&self, py: Python<'_>) -> HashMap<String, String> {
+        py.detach(|| {
+            let mut display = HashMap::new();
+            self.for_each_stem(|stem, forms| {
+                if let Some(form) = display_form(forms) {
+                    display.insert(stem.to_owned(), form.to_owned());
+                }
+            });
+            display
+        })
+    }
+
+    // Everything recorded, as {stem: {surface form: count}}
+    fn export(&self, py: Python<'_>) -> HashMap<String, Forms> {
+        py.detach(|| {
+            let mut counts = HashMap::new();
+            self.for_each_stem(|stem, forms| {
+                counts.insert(stem.to_owned(), forms.clone());
+            });
+            counts
+        })
+    }
+
+    fn clear(&self) {
+        for shard in self.shards.iter() {
+            shard.lock().unwrap_or_else(PoisonError::into_inner).clear();
+        }
+    }
+
+    // Number of distinct stems recorded
+    fn __len__(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|shard| shard.lock().unwrap_or_else(PoisonError::into_inner).len())
+            .sum()
+    }
+
+    // Pickled with its counts; what a copy records afterwards (in a worker
+    // process, say) is not seen by the original
+    fn __reduce__<'py>(
+        &self,
+        py: Python<'py>,
+    ) -> PyResult<(Bound<'py, PyAny>, Bound<'py, PyTuple>)> {
+        let class = py.get_type::<StemRecorder>().into_any();
+        Ok((class, (self.export(py),).into_pyobject(py)?))
+    }
+
+    fn __repr__(&self) -> String {
+        format!("<StemRecorder at {:p}>", self)
+    }
+}
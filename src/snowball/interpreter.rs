@@ -0,0 +1,565 @@
+// Runs a `Program` over one word. Positions are character indices. Cursor
+// saves and restores follow the generated C code: absolute in forward mode,
+// counted from the limit in backward mode, where edits happen to the left of
+// the saved position. Programs are untrusted, and the release build aborts on
+// a panic, so positions are clamped to the string rather than trusted: a
+// program that edits the string under a saved cursor gets a wrong stem, not a
+// crash.
+
+use super::{AssignOp, BinOp, Command, Expr, Program, RelOp, Text};
+
+// Loop iterations and routine calls a word may take before a runaway program
+// (such as `repeat true`) is abandoned and the word left unstemmed
+const STEP_BUDGET: usize = 1_000_000;
+const MAX_CALL_DEPTH: usize = 512;
+
+struct Env<'p> {
+    program: &'p Program,
+    s: Vec<char>,
+    c: usize,
+    l: usize,
+    lb: usize,
+    bra: usize,
+    ket: usize,
+    backward: bool,
+    integers: Vec<i64>,
+    strings: Vec<Vec<char>>,
+    booleans: Vec<bool>,
+    budget: usize,
+    depth: usize,
+}
+
+pub fn run(program: &Program, word: &str) -> Option<String> {
+    let s: Vec<char> = word.chars().collect();
+    let mut env = Env {
+        program,
+        c: 0,
+        l: s.len(),
+        lb: 0,
+        bra: 0,
+        ket: s.len(),
+        s,
+        backward: false,
+        integers: vec![0; program.integers],
+        strings: vec![Vec::new(); program.strings],
+        booleans: vec![false; program.booleans],
+        budget: STEP_BUDGET,
+        depth: 0,
+    };
+    // An external defined in backward mode starts at the end of the word
+    if program.routines[program.entry].backward {
+        env.c = env.l;
+    }
+    env.call(program.entry);
+    (env.budget > 0).then(|| env.s.into_iter().collect())
+}
+
+impl Env<'_> {
+    // Charge one step; false once the budget is spent
+    fn tick(&mut self) -> bool {
+        self.budget = self.budget.saturating_sub(1);
+        self.budget > 0
+    }
+
+    fn save(&self) -> usize {
+        if self.backward {
+            self.l.saturating_sub(self.c)
+        } else {
+            self.c
+        }
+    }
+
+    // A failed command may have shortened the string since the save
+    fn restore(&mut self, saved: usize) {
+        self.c = if self.backward {
+            self.l.saturating_sub(saved)
+        } else {
+            saved
+        };
+        self.clamp_cursor();
+    }
+
+    // Keep the cursor within `lb..=l` (`0..=l` in forward mode, where `lb`
+    // may be left over from an earlier `backwards`)
+    fn clamp_cursor(&mut self) {
+        self.l = self.l.min(self.s.len());
+        let low = if self.backward {
+            self.lb.min(self.l)
+        } else {
+            0
+        };
+        self.c = self.c.clamp(low, self.l);
+    }
+
+    // Move one character in the current direction
+    fn step(&mut self) -> bool {
+        if self.backward {
+            if self.c <= self.lb {
+                return false;
+            }
+            self.c -= 1;
+        } else {
+            if self.c >= self.l {
+                return false;
+            }
+            self.c += 1;
+        }
+        true
+    }
+
+    fn call(&mut self, routine: usize) -> bool {
+        let routine = &self.program.routines[routine];
+        let Some(body) = &routine.body else {
+            return false;
+        };
+        if !self.tick() || self.depth >= MAX_CALL_DEPTH {
+            self.budget = 0;
+            return false;
+        }
+        let backward = std::mem::replace(&mut self.backward, routine.backward);
+        self.depth += 1;
+        let mut among_var = 0;
+        let result = self.exec(body, &mut among_var);
+        self.depth -= 1;
+        self.backward = backward;
+        result
+    }
+
+    fn eq_s(&mut self, text: &[char]) -> bool {
+        let len = text.len();
+        if self.backward {
+            if self.c < self.lb + len || self.s[self.c - len..self.c] != *text {
+                return false;
+            }
+            self.c -= len;
+        } else {
+            if self.c + len > self.l || self.s[self.c..self.c + len] != *text {
+                return false;
+            }
+            self.c += len;
+        }
+        true
+    }
+
+    fn in_grouping(&mut self, grouping: usize, negated: bool) -> bool {
+        let at = if self.backward {
+            if self.c <= self.lb {
+                return false;
+            }
+            self.c - 1
+        } else {
+            if self.c >= self.l {
+                return false;
+            }
+            self.c
+        };
+        let found = self.program.groupings[grouping]
+            .binary_search(&self.s[at])
+            .is_ok();
+        found != negated && self.step()
+    }
+
+    // Replace `bra..ket` with `text`, returning the change in length
+    fn replace(&mut self, bra: usize, ket: usize, text: &[char]) -> Option<isize> {
+        if bra > ket || ket > self.l {
+            return None;
+        }
+        self.s.splice(bra..ket, text.iter().copied());
+        let adjustment = text.len() as isize - (ket - bra) as isize;
+        self.l = self.l.checked_add_signed(adjustment)?;
+        if self.c >= ket {
+            self.c = self.c.checked_add_signed(adjustment)?;
+        } else if self.c > bra {
+            self.c = bra;
+        }
+        self.clamp_cursor();
+        Some(adjustment)
+    }
+
+    fn insert(&mut self, text: &[char], keep_cursor: bool) -> bool {
+        let at = self.c;
+        let Some(adjustment) = self.replace(at, at, text) else {
+            return false;
+        };
+        if at <= self.bra {
+            self.bra = self.bra.saturating_add_signed(adjustment);
+        }
+        if at <= self.ket {
+            self.ket = self.ket.saturating_add_signed(adjustment);
+        }
+        // `insert` leaves the cursor before the new text in backward mode,
+        // `attach` does so in forward mode
+        if keep_cursor != self.backward {
+            self.c = at;
+        }
+        true
+    }
+
+    fn text(&self, text: &Text) -> Vec<char> {
+        match text {
+            Text::Literal(text) => text.clone(),
+            Text::Var(index) => self.strings[*index].clone(),
+        }
+    }
+
+    fn eval(&self, expr: &Expr) -> i64 {
+        match expr {
+            Expr::Number(number) => *number,
+            Expr::Int(index) => self.integers[*index],
+            Expr::Cursor => self.c as i64,
+            Expr::Limit => (if self.backward { self.lb } else { self.l }) as i64,
+            Expr::Size => self.s.len() as i64,
+            Expr::SizeOf(index) => self.strings[*index].len() as i64,
+            Expr::Neg(expr) => self.eval(expr).wrapping_neg(),
+            Expr::Binary(left, op, right) => {
+                let (left, right) = (self.eval(left), self.eval(right));
+                match op {
+                    BinOp::Add => left.wrapping_add(right),
+                    BinOp::Sub => left.wrapping_sub(right),
+                    BinOp::Mul => left.wrapping_mul(right),
+                    BinOp::Div => left.checked_div(right).unwrap_or(0),
+                }
+            }
+        }
+    }
+
+    // Position an arithmetic expression names, if it is within the string
+    fn position(&self, expr: &Expr) -> Option<usize> {
+        usize::try_from(self.eval(expr))
+            .ok()
+            .filter(|&pos| pos <= self.s.len())
+    }
+
+    fn find_among(&mut self, among: usize) -> usize {
+        let among = &self.program.amongs[among];
+        let start = self.c;
+        for entry in &among.entries {
+            if !self.eq_s(&entry.text) {
+                continue;
+            }
+            if let Some(condition) = entry.condition {
+                let end = self.c;
+                let matched = self.call(condition);
+                self.c = end;
+                if !matched {
+                    self.c = start;
+                    continue;
+                }
+            }
+            return entry.action + 1;
+        }
+        0
+    }
+
+    fn exec(&mut self, command: &Command, among_var: &mut usize) -> bool {
+        if self.budget == 0 {
+            return false;
+        }
+        match command {
+            Command::Seq(commands) => {
+                for command in commands {
+                    if !self.exec(command, among_var) {
+                        return false;
+                    }
+                }
+                true
+            }
+            Command::Or(commands) => {
+                let saved = self.save();
+                for command in commands {
+                    if self.exec(command, among_var) {
+                        return true;
+                    }
+                    self.restore(saved);
+                }
+                false
+            }
+            Command::And(commands) => {
+                let saved = self.save();
+                for (i, command) in commands.iter().enumerate() {
+                    if i > 0 {
+                        self.restore(saved);
+                    }
+                    if !self.exec(command, among_var) {
+                        return false;
+                    }
+                }
+                true
+            }
+            Command::Not(command) => {
+                let saved = self.save();
+                if self.exec(command, among_var) {
+                    return false;
+                }
+                self.restore(saved);
+                true
+            }
+            Command::Test(command) => {
+                let saved = self.save();
+                let result = self.exec(command, among_var);
+                self.restore(saved);
+                result
+            }
+            Command::Try(command) => {
+                let saved = self.save();
+                if !self.exec(command, among_var) {
+                    self.restore(saved);
+                }
+                true
+            }
+            Command::Do(command) => {
+                let saved = self.save();
+                self.exec(command, among_var);
+                self.restore(saved);
+                true
+            }
+            Command::Fail(command) => {
+                self.exec(command, among_var);
+                false
+            }
+            Command::Goto(command) => self.go(command, among_var, false),
+            Command::Gopast(command) => self.go(command, among_var, true),
+            Command::Repeat(command) => self.repeat(command, among_var),
+            Command::Loop(count, command) => self.times(count, command, among_var),
+            Command::Atleast(count, command) => {
+                self.times(count, command, among_var) && self.repeat(command, among_var)
+            }
+            Command::Backwards(command) => {
+                let backward = std::mem::replace(&mut self.backward, true);
+                self.lb = self.c;
+                self.c = self.l;
+                let result = self.exec(command, among_var);
+                self.backward = backward;
+                if result {
+                    self.c = self.lb;
+                }
+                result
+            }
+            Command::Setlimit(limit, command) => {
+                let saved = self.save();
+                if !self.exec(limit, among_var) {
+                    return false;
+                }
+                let result = if self.backward {
+                    let lb = std::mem::replace(&mut self.lb, self.c);
+                    self.restore(saved);
+                    let result = self.exec(command, among_var);
+                    self.lb = lb;
+                    result
+                } else {
+                    let beyond = self.l.saturating_sub(self.c);
+                    self.l = self.c;
+                    self.restore(saved);
+                    let result = self.exec(command, among_var);
+                    self.l = self.l.saturating_add(beyond);
+                    result
+                };
+                self.clamp_cursor();
+                result
+            }
+            Command::Literal(text) => self.eq_s(text),
+            Command::StringVar(index) => {
+                let text = self.strings[*index].clone();
+                self.eq_s(&text)
+            }
+            Command::Grouping { grouping, negated } => self.in_grouping(*grouping, *negated),
+            Command::Call(routine) => self.call(*routine),
+            Command::BoolTest(index) => self.booleans[*index],
+            Command::Set(index) => {
+                self.booleans[*index] = true;
+                true
+            }
+            Command::Unset(index) => {
+                self.booleans[*index] = false;
+                true
+            }
+            Command::Bra => {
+                if self.backward {
+                    self.ket = self.c;
+                } else {
+                    self.bra = self.c;
+                }
+                true
+            }
+            Command::Ket => {
+                if self.backward {
+                    self.bra = self.c;
+                } else {
+                    self.ket = self.c;
+                }
+                true
+            }
+            Command::SliceFrom(text) => {
+                let text = self.text(text);
+                self.replace(self.bra, self.ket, &text).is_some()
+            }
+            Command::Delete => self.replace(self.bra, self.ket, &[]).is_some(),
+            // `=> s`: the string up to the limit
+            Command::AssignTo(index) => {
+                self.strings[*index] = self.s[..self.l].to_vec();
+                true
+            }
+            Command::OnString(index, command) => self.on_string(*index, command, among_var),
+            Command::SliceTo(index) => {
+                if self.bra > self.ket || self.ket > self.l {
+                    return false;
+                }
+                self.strings[*index] = self.s[self.bra..self.ket].to_vec();
+                true
+            }
+            Command::Insert(text) => {
+                let text = self.text(text);
+                self.insert(&text, false)
+            }
+            Command::Attach(text) => {
+                let text = self.text(text);
+                self.insert(&text, true)
+            }
+            Command::Hop(count) => {
+                let Ok(count) = usize::try_from(self.eval(count)) else {
+                    return false;
+                };
+                let target = if self.backward {
+                    self.c.checked_sub(count).filter(|&c| c >= self.lb)
+                } else {
+                    self.c.checked_add(count).filter(|&c| c <= self.l)
+                };
+                match target {
+                    Some(target) => {
+                        self.c = target;
+                        true
+                    }
+                    None => false,
+                }
+            }
+            Command::Next => self.step(),
+            Command::Setmark(index) => {
+                self.integers[*index] = self.c as i64;
+                true
+            }
+            Command::Tomark(mark) => {
+                let Some(mark) = self.position(mark) else {
+                    return false;
+                };
+                let reachable = if self.backward {
+                    mark >= self.lb && mark <= self.c
+                } else {
+                    mark <= self.l && mark >= self.c
+                };
+                if reachable {
+                    self.c = mark;
+                }
+                reachable
+            }
+            Command::Atmark(mark) => self.position(mark) == Some(self.c),
+            Command::Tolimit => {
+                self.c = if self.backward { self.lb } else { self.l };
+                true
+            }
+            Command::Atlimit => self.c == if self.backward { self.lb } else { self.l },
+            Command::True => true,
+            Command::False => false,
+            Command::Substring(among) => {
+                *among_var = self.find_among(*among);
+                *among_var != 0
+            }
+            Command::Among { among, matched } => {
+                if !matched {
+                    *among_var = self.find_among(*among);
+                }
+                let among = &self.program.amongs[*among];
+                if among
+                    .starter
+                    .as_ref()
+                    .is_some_and(|starter| !self.exec(starter, among_var))
+                {
+                    return false;
+                }
+                match among.actions.get(among_var.wrapping_sub(1)) {
+                    Some(action) => self.exec(action, among_var),
+                    None => false,
+                }
+            }
+            Command::IntAssign(index, op, expr) => {
+                let value = self.eval(expr);
+                let variable = &mut self.integers[*index];
+                *variable = match op {
+                    AssignOp::Set => value,
+                    AssignOp::Add => variable.wrapping_add(value),
+                    AssignOp::Sub => variable.wrapping_sub(value),
+                    AssignOp::Mul => variable.wrapping_mul(value),
+                    AssignOp::Div => variable.checked_div(value).unwrap_or(0),
+                };
+                true
+            }
+            Command::IntTest(left, op, right) => {
+                let (left, right) = (self.eval(left), self.eval(right));
+                match op {
+                    RelOp::Eq => left == right,
+                    RelOp::Ne => left != right,
+                    RelOp::Lt => left < right,
+                    RelOp::Le => left <= right,
+                    RelOp::Gt => left > right,
+                    RelOp::Ge => left >= right,
+                }
+            }
+        }
+    }
+
+    // `$ s C`: run `command` on string variable `index` in place of the word,
+    // as the generated C code does by swapping the whole environment
+    fn on_string(&mut self, index: usize, command: &Command, among_var: &mut usize) -> bool {
+        let string = std::mem::take(&mut self.strings[index]);
+        let l = string.len();
+        let s = std::mem::replace(&mut self.s, string);
+        let saved = (self.c, self.l, self.lb, self.bra, self.ket);
+        (self.c, self.l, self.lb, self.bra, self.ket) = (0, l, 0, 0, l);
+        let result = self.exec(command, among_var);
+        self.strings[index] = std::mem::replace(&mut self.s, s);
+        (self.c, self.l, self.lb, self.bra, self.ket) = saved;
+        result
+    }
+
+    // Advance until `command` matches, leaving the cursor before the match
+    // (`goto`) or after it (`gopast`)
+    fn go(&mut self, command: &Command, among_var: &mut usize, past: bool) -> bool {
+        loop {
+            if !self.tick() {
+                return false;
+            }
+            let saved = self.save();
+            if self.exec(command, among_var) {
+                if !past {
+                    self.restore(saved);
+                }
+                return true;
+            }
+            self.restore(saved);
+            if !self.step() {
+                return false;
+            }
+        }
+    }
+
+    fn times(&mut self, count: &Expr, command: &Command, among_var: &mut usize) -> bool {
+        for _ in 0..self.eval(count) {
+            if !self.tick() || !self.exec(command, among_var) {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn repeat(&mut self, command: &Command, among_var: &mut usize) -> bool {
+        loop {
+            if !self.tick() {
+                return false;
+            }
+            let saved = self.save();
+            if !self.exec(command, among_var) {
+                self.restore(saved);
+                return true;
+            }
+        }
+    }
+}
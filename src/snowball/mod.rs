@@ -0,0 +1,158 @@
+// A runtime interpreter for Snowball (.sbl) stemming programs, so tweaked
+// algorithms can be loaded without rebuilding the extension. The source is
+// parsed into a tree once; every word is then run over it with the same
+// semantics as the C code the Snowball compiler generates.
+
+mod interpreter;
+mod parser;
+
+use std::borrow::Cow;
+
+// String-manipulating commands (`C` in the Snowball manual)
+pub enum Command {
+    // `( C1 C2 ... )`: every command in turn
+    Seq(Vec<Command>),
+    Or(Vec<Command>),
+    And(Vec<Command>),
+    Not(Box<Command>),
+    Test(Box<Command>),
+    Try(Box<Command>),
+    Do(Box<Command>),
+    Fail(Box<Command>),
+    Goto(Box<Command>),
+    Gopast(Box<Command>),
+    Repeat(Box<Command>),
+    Backwards(Box<Command>),
+    Loop(Expr, Box<Command>),
+    Atleast(Expr, Box<Command>),
+    Setlimit(Box<Command>, Box<Command>),
+    // Tests for a string at the cursor
+    Literal(Vec<char>),
+    StringVar(usize),
+    Grouping { grouping: usize, negated: bool },
+    Call(usize),
+    BoolTest(usize),
+    Set(usize),
+    Unset(usize),
+    // `[` and `]`
+    Bra,
+    Ket,
+    SliceFrom(Text),
+    SliceTo(usize),
+    // `=> s`
+    AssignTo(usize),
+    // `$ s C`: `C` run on string variable `s` instead of the word
+    OnString(usize, Box<Command>),
+    Delete,
+    // `insert`/`<+` and `attach`
+    Insert(Text),
+    Attach(Text),
+    Hop(Expr),
+    Next,
+    Setmark(usize),
+    Tomark(Expr),
+    Atmark(Expr),
+    Tolimit,
+    Atlimit,
+    True,
+    False,
+    // `substring`, matching the `among` at this index
+    Substring(usize),
+    // `among`; `matched` is set when a `substring` already did the matching
+    Among { among: usize, matched: bool },
+    IntAssign(usize, AssignOp, Expr),
+    IntTest(Expr, RelOp, Expr),
+}
+
+// A string argument: a literal or a string variable
+pub enum Text {
+    Literal(Vec<char>),
+    Var(usize),
+}
+
+// Arithmetic expressions (`AE`)
+pub enum Expr {
+    Number(i64),
+    Int(usize),
+    Cursor,
+    Limit,
+    Size,
+    SizeOf(usize),
+    Neg(Box<Expr>),
+    Binary(Box<Expr>, BinOp, Box<Expr>),
+}
+
+#[derive(Clone, Copy)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+#[derive(Clone, Copy)]
+pub enum AssignOp {
+    Set,
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+#[derive(Clone, Copy)]
+pub enum RelOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+pub struct AmongEntry {
+    text: Vec<char>,
+    // Routine that must also succeed for the entry to match
+    condition: Option<usize>,
+    // Index into `Among::actions`
+    action: usize,
+}
+
+pub struct Among {
+    // Longest first, so the first match is the one Snowball picks
+    entries: Vec<AmongEntry>,
+    // Run after a match, before its action
+    starter: Option<Command>,
+    actions: Vec<Command>,
+}
+
+pub struct Routine {
+    name: String,
+    body: Option<Command>,
+    backward: bool,
+}
+
+pub struct Program {
+    routines: Vec<Routine>,
+    // Sorted characters of each grouping
+    groupings: Vec<Vec<char>>,
+    amongs: Vec<Among>,
+    integers: usize,
+    strings: usize,
+    booleans: usize,
+    // The `stem` external (or the only external)
+    entry: usize,
+}
+
+impl Program {
+    // Parse a Snowball source file. Errors carry the line they were found on.
+    pub fn compile(source: &str) -> Result<Program, String> {
+        parser::parse(source)
+    }
+
+    pub fn stem<'a>(&self, word: &'a str) -> Cow<'a, str> {
+        match interpreter::run(self, word) {
+            Some(stem) if stem != word => Cow::Owned(stem),
+            _ => Cow::Borrowed(word),
+        }
+    }
+}
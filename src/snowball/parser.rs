@@ -0,0 +1,860 @@
+// Snowball source -> `Program`. The lexer reads one token at a time because
+// `stringescapes` and `stringdef` change how later string literals are read.
+
+use super::{Among, AmongEntry, AssignOp, BinOp, Command, Expr, Program, RelOp, Routine, Text};
+use std::collections::HashMap;
+
+// Longest first, so `<-` is not read as `<` followed by `-`
+const SYMBOLS: [&str; 26] = [
+    "->", "<-", "<+", "==", "!=", ">=", "<=", "+=", "-=", "*=", "/=", "=>", "(", ")", "[", "]",
+    "$", "=", "<", ">", "+", "-", "*", "/", "?", ",",
+];
+
+#[derive(Clone, PartialEq, Debug)]
+enum Token {
+    Name(String),
+    Number(i64),
+    Literal(Vec<char>),
+    Symbol(&'static str),
+    End,
+}
+
+impl Token {
+    fn is_symbol(&self, symbol: &str) -> bool {
+        matches!(self, Token::Symbol(s) if *s == symbol)
+    }
+
+    fn is_name(&self, name: &str) -> bool {
+        matches!(self, Token::Name(n) if n == name)
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Token::Name(name) => format!("'{}'", name),
+            Token::Number(number) => number.to_string(),
+            Token::Literal(text) => format!("'{}'", text.iter().collect::<String>()),
+            Token::Symbol(symbol) => format!("'{}'", symbol),
+            Token::End => "end of file".to_owned(),
+        }
+    }
+}
+
+struct Lexer {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+    // Characters opening and closing an escape in string literals
+    escapes: Option<(char, char)>,
+    stringdefs: HashMap<String, Vec<char>>,
+}
+
+impl Lexer {
+    fn peek_char(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek_char()?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    fn skip_space(&mut self) -> Result<(), String> {
+        loop {
+            match (self.peek_char(), self.chars.get(self.pos + 1)) {
+                (Some(c), _) if c.is_whitespace() => {
+                    self.bump();
+                }
+                (Some('/'), Some('/')) => while self.bump().is_some_and(|c| c != '\n') {},
+                (Some('/'), Some('*')) => {
+                    self.pos += 2;
+                    loop {
+                        match self.bump() {
+                            Some('*') if self.peek_char() == Some('/') => {
+                                self.bump();
+                                break;
+                            }
+                            Some(_) => {}
+                            None => return Err("unterminated comment".to_owned()),
+                        }
+                    }
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    fn token(&mut self) -> Result<Token, String> {
+        self.skip_space()?;
+        let Some(c) = self.peek_char() else {
+            return Ok(Token::End);
+        };
+        if c.is_alphabetic() || c == '_' {
+            let start = self.pos;
+            while self
+                .peek_char()
+                .is_some_and(|c| c.is_alphanumeric() || c == '_')
+            {
+                self.bump();
+            }
+            return Ok(Token::Name(self.chars[start..self.pos].iter().collect()));
+        }
+        if c.is_ascii_digit() {
+            let mut number: i64 = 0;
+            while let Some(digit) = self.peek_char().and_then(|c| c.to_digit(10)) {
+                number = number
+                    .checked_mul(10)
+                    .and_then(|n| n.checked_add(i64::from(digit)))
+                    .ok_or("number too large")?;
+                self.bump();
+            }
+            return Ok(Token::Number(number));
+        }
+        if c == '\'' {
+            self.bump();
+            return self.literal().map(Token::Literal);
+        }
+        for symbol in SYMBOLS {
+            if symbol
+                .chars()
+                .enumerate()
+                .all(|(i, c)| self.chars.get(self.pos + i) == Some(&c))
+            {
+                self.pos += symbol.chars().count();
+                return Ok(Token::Symbol(symbol));
+            }
+        }
+        Err(format!("unexpected character '{}'", c))
+    }
+
+    // The rest of a string literal, after its opening quote
+    fn literal(&mut self) -> Result<Vec<char>, String> {
+        let mut text = Vec::new();
+        loop {
+            match self.bump() {
+                Some('\'') => return Ok(text),
+                Some(c) if self.escapes.is_some_and(|(open, _)| open == c) => {
+                    let (open, close) = self.escapes.unwrap();
+                    let mut name = String::new();
+                    loop {
+                        match self.bump() {
+                            Some(c) if c == close => break,
+                            Some('\n') | None => {
+                                return Err("unterminated string escape".to_owned())
+                            }
+                            Some(c) => name.push(c),
+                        }
+                    }
+                    text.extend(self.escape(&name, open)?);
+                }
+                Some('\n') | None => return Err("unterminated string".to_owned()),
+                Some(c) => text.push(c),
+            }
+        }
+    }
+
+    fn escape(&self, name: &str, open: char) -> Result<Vec<char>, String> {
+        if let Some(text) = self.stringdefs.get(name) {
+            return Ok(text.clone());
+        }
+        if name == "'" {
+            return Ok(vec!['\'']);
+        }
+        // `{{}` stands for the opening character itself
+        if name.len() == 1 && name.starts_with(open) {
+            return Ok(vec![open]);
+        }
+        name.strip_prefix("U+")
+            .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+            .and_then(char::from_u32)
+            .map(|c| vec![c])
+            .ok_or_else(|| format!("undefined stringdef '{}'", name))
+    }
+
+    // The next whitespace-delimited run of characters, for the name in
+    // `stringdef`
+    fn raw_char(&mut self) -> Result<char, String> {
+        self.skip_space()?;
+        self.bump()
+            .ok_or_else(|| "unexpected end of file".to_owned())
+    }
+
+    fn raw_word(&mut self) -> Result<String, String> {
+        self.skip_space()?;
+        let mut word = String::new();
+        while let Some(c) = self.peek_char().filter(|c| !c.is_whitespace()) {
+            word.push(c);
+            self.bump();
+        }
+        if word.is_empty() {
+            return Err("unexpected end of file".to_owned());
+        }
+        Ok(word)
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Name {
+    Routine(usize),
+    Grouping(usize),
+    Integer(usize),
+    String(usize),
+    Boolean(usize),
+}
+
+struct Parser {
+    lexer: Lexer,
+    peeked: Option<Token>,
+    // Line of the last token read, for errors
+    line: usize,
+    names: HashMap<String, Name>,
+    routines: Vec<Routine>,
+    externals: Vec<usize>,
+    groupings: Vec<Option<Vec<char>>>,
+    amongs: Vec<Among>,
+    integers: usize,
+    strings: usize,
+    booleans: usize,
+    backward: bool,
+    // The `among` a `substring` in the current routine is waiting for
+    substring: Option<usize>,
+}
+
+pub fn parse(source: &str) -> Result<Program, String> {
+    let mut parser = Parser {
+        lexer: Lexer {
+            chars: source.chars().collect(),
+            pos: 0,
+            line: 1,
+            escapes: None,
+            stringdefs: HashMap::new(),
+        },
+        peeked: None,
+        line: 1,
+        names: HashMap::new(),
+        routines: Vec::new(),
+        externals: Vec::new(),
+        groupings: Vec::new(),
+        amongs: Vec::new(),
+        integers: 0,
+        strings: 0,
+        booleans: 0,
+        backward: false,
+        substring: None,
+    };
+    parser
+        .program()
+        .map_err(|message| format!("line {}: {}", parser.line, message))
+}
+
+impl Parser {
+    fn next(&mut self) -> Result<Token, String> {
+        if let Some(token) = self.peeked.take() {
+            return Ok(token);
+        }
+        let token = self.lexer.token();
+        self.line = self.lexer.line;
+        token
+    }
+
+    fn peek(&mut self) -> Result<&Token, String> {
+        if self.peeked.is_none() {
+            self.peeked = Some(self.next()?);
+        }
+        Ok(self.peeked.as_ref().unwrap())
+    }
+
+    fn expect_symbol(&mut self, symbol: &str) -> Result<(), String> {
+        match self.next()? {
+            token if token.is_symbol(symbol) => Ok(()),
+            token => Err(format!("expected '{}', found {}", symbol, token.describe())),
+        }
+    }
+
+    fn name(&mut self) -> Result<String, String> {
+        match self.next()? {
+            Token::Name(name) => Ok(name),
+            token => Err(format!("expected a name, found {}", token.describe())),
+        }
+    }
+
+    fn lookup(&mut self) -> Result<(String, Name), String> {
+        let name = self.name()?;
+        match self.names.get(&name) {
+            Some(&kind) => Ok((name, kind)),
+            None => Err(format!("'{}' is not declared", name)),
+        }
+    }
+
+    fn integer(&mut self) -> Result<usize, String> {
+        match self.lookup()? {
+            (_, Name::Integer(index)) => Ok(index),
+            (name, _) => Err(format!("'{}' is not an integer", name)),
+        }
+    }
+
+    fn string(&mut self) -> Result<usize, String> {
+        match self.lookup()? {
+            (_, Name::String(index)) => Ok(index),
+            (name, _) => Err(format!("'{}' is not a string", name)),
+        }
+    }
+
+    fn boolean(&mut self) -> Result<usize, String> {
+        match self.lookup()? {
+            (_, Name::Boolean(index)) => Ok(index),
+            (name, _) => Err(format!("'{}' is not a boolean", name)),
+        }
+    }
+
+    fn grouping(&mut self) -> Result<usize, String> {
+        match self.lookup()? {
+            (_, Name::Grouping(index)) => Ok(index),
+            (name, _) => Err(format!("'{}' is not a grouping", name)),
+        }
+    }
+
+    fn program(&mut self) -> Result<Program, String> {
+        loop {
+            match self.next()? {
+                Token::End => break,
+                token => self.declaration(token)?,
+            }
+        }
+
+        if let Some(routine) = self.routines.iter().find(|r| r.body.is_none()) {
+            return Err(format!(
+                "routine '{}' is declared but not defined",
+                routine.name
+            ));
+        }
+        let entry = match self.externals.as_slice() {
+            [entry] => *entry,
+            externals => *externals
+                .iter()
+                .find(|&&index| self.routines[index].name == "stem")
+                .ok_or("no 'stem' external to run")?,
+        };
+        Ok(Program {
+            routines: std::mem::take(&mut self.routines),
+            groupings: self
+                .groupings
+                .iter_mut()
+                .map(|grouping| grouping.take().unwrap_or_default())
+                .collect(),
+            amongs: std::mem::take(&mut self.amongs),
+            integers: self.integers,
+            strings: self.strings,
+            booleans: self.booleans,
+            entry,
+        })
+    }
+
+    fn declaration(&mut self, token: Token) -> Result<(), String> {
+        let Token::Name(keyword) = token else {
+            return Err(format!("unexpected {}", token.describe()));
+        };
+        match keyword.as_str() {
+            "strings" | "integers" | "booleans" | "routines" | "externals" | "groupings" => {
+                self.expect_symbol("(")?;
+                loop {
+                    match self.next()? {
+                        token if token.is_symbol(")") => return Ok(()),
+                        Token::Name(name) => self.declare(&keyword, name)?,
+                        token => {
+                            return Err(format!("expected a name, found {}", token.describe()))
+                        }
+                    }
+                }
+            }
+            "stringescapes" => {
+                let open = self.lexer.raw_char()?;
+                let close = self.lexer.raw_char()?;
+                self.lexer.escapes = Some((open, close));
+                Ok(())
+            }
+            "stringdef" => {
+                let name = self.lexer.raw_word()?;
+                let value = match self.next()? {
+                    Token::Name(radix) if radix == "hex" || radix == "decimal" => {
+                        let Token::Literal(digits) = self.next()? else {
+                            return Err("expected a string after stringdef".to_owned());
+                        };
+                        let digits: String = digits.into_iter().collect();
+                        digits
+                            .split_whitespace()
+                            .map(|code| {
+                                u32::from_str_radix(code, if radix == "hex" { 16 } else { 10 })
+                                    .ok()
+                                    .and_then(char::from_u32)
+                                    .ok_or_else(|| format!("invalid character code '{}'", code))
+                            })
+                            .collect::<Result<_, _>>()?
+                    }
+                    Token::Literal(text) => text,
+                    token => {
+                        return Err(format!(
+                            "expected a string after stringdef, found {}",
+                            token.describe()
+                        ))
+                    }
+                };
+                self.lexer.stringdefs.insert(name, value);
+                Ok(())
+            }
+            "define" => self.define(),
+            "backwardmode" => {
+                self.expect_symbol("(")?;
+                self.backward = true;
+                loop {
+                    match self.next()? {
+                        token if token.is_symbol(")") => break,
+                        token => self.declaration(token)?,
+                    }
+                }
+                self.backward = false;
+                Ok(())
+            }
+            _ => Err(format!("unexpected '{}'", keyword)),
+        }
+    }
+
+    fn declare(&mut self, kind: &str, name: String) -> Result<(), String> {
+        if self.names.contains_key(&name) {
+            return Err(format!("'{}' is declared twice", name));
+        }
+        let declared = match kind {
+            "strings" => Name::String(post_increment(&mut self.strings)),
+            "integers" => Name::Integer(post_increment(&mut self.integers)),
+            "booleans" => Name::Boolean(post_increment(&mut self.booleans)),
+            "groupings" => {
+                self.groupings.push(None);
+                Name::Grouping(self.groupings.len() - 1)
+            }
+            _ => {
+                if kind == "externals" {
+                    self.externals.push(self.routines.len());
+                }
+                self.routines.push(Routine {
+                    name: name.clone(),
+                    body: None,
+                    backward: false,
+                });
+                Name::Routine(self.routines.len() - 1)
+            }
+        };
+        self.names.insert(name, declared);
+        Ok(())
+    }
+
+    fn define(&mut self) -> Result<(), String> {
+        match self.lookup()? {
+            (name, Name::Routine(index)) => {
+                if self.routines[index].body.is_some() {
+                    return Err(format!("'{}' is defined twice", name));
+                }
+                match self.next()? {
+                    token if token.is_name("as") => {}
+                    token => return Err(format!("expected 'as', found {}", token.describe())),
+                }
+                self.substring = None;
+                let body = self.connected()?;
+                if self.substring.is_some() {
+                    return Err("'substring' without a following 'among'".to_owned());
+                }
+                self.routines[index].body = Some(body);
+                self.routines[index].backward = self.backward;
+                Ok(())
+            }
+            (name, Name::Grouping(index)) => {
+                if self.groupings[index].is_some() {
+                    return Err(format!("'{}' is defined twice", name));
+                }
+                let mut chars = self.grouping_operand()?;
+                loop {
+                    let subtract = match self.peek()? {
+                        token if token.is_symbol("+") => false,
+                        token if token.is_symbol("-") => true,
+                        _ => break,
+                    };
+                    self.next()?;
+                    let operand = self.grouping_operand()?;
+                    if subtract {
+                        chars.retain(|c| !operand.contains(c));
+                    } else {
+                        chars.extend(operand);
+                    }
+                }
+                chars.sort_unstable();
+                chars.dedup();
+                self.groupings[index] = Some(chars);
+                Ok(())
+            }
+            (name, _) => Err(format!("'{}' is not a routine or grouping", name)),
+        }
+    }
+
+    fn grouping_operand(&mut self) -> Result<Vec<char>, String> {
+        match self.next()? {
+            Token::Literal(text) => Ok(text),
+            Token::Name(name) => match self.names.get(&name) {
+                Some(&Name::Grouping(index)) => self.groupings[index]
+                    .clone()
+                    .ok_or_else(|| format!("grouping '{}' is used before it is defined", name)),
+                _ => Err(format!("'{}' is not a grouping", name)),
+            },
+            token => Err(format!("expected a grouping, found {}", token.describe())),
+        }
+    }
+
+    // A command followed by any number of `and`/`or` connections, which
+    // have equal precedence and group to the left
+    fn connected(&mut self) -> Result<Command, String> {
+        let mut command = self.command()?;
+        loop {
+            let or = match self.peek()? {
+                token if token.is_name("or") => true,
+                token if token.is_name("and") => false,
+                _ => return Ok(command),
+            };
+            self.next()?;
+            let next = self.command()?;
+            command = match (command, or) {
+                (Command::Or(mut commands), true) => {
+                    commands.push(next);
+                    Command::Or(commands)
+                }
+                (Command::And(mut commands), false) => {
+                    commands.push(next);
+                    Command::And(commands)
+                }
+                (command, true) => Command::Or(vec![command, next]),
+                (command, false) => Command::And(vec![command, next]),
+            };
+        }
+    }
+
+    // The commands of a `( ... )` list, after the opening bracket
+    fn list(&mut self) -> Result<Command, String> {
+        let mut commands = Vec::new();
+        while !self.peek()?.is_symbol(")") {
+            commands.push(self.connected()?);
+        }
+        self.next()?;
+        Ok(Command::Seq(commands))
+    }
+
+    fn boxed(&mut self) -> Result<Box<Command>, String> {
+        self.command().map(Box::new)
+    }
+
+    fn text(&mut self) -> Result<Text, String> {
+        match self.next()? {
+            Token::Literal(text) => Ok(Text::Literal(text)),
+            Token::Name(name) => match self.names.get(&name) {
+                Some(&Name::String(index)) => Ok(Text::Var(index)),
+                _ => Err(format!("'{}' is not a string", name)),
+            },
+            token => Err(format!("expected a string, found {}", token.describe())),
+        }
+    }
+
+    fn command(&mut self) -> Result<Command, String> {
+        let name = match self.next()? {
+            Token::Literal(text) => return Ok(Command::Literal(text)),
+            Token::Symbol("(") => return self.list(),
+            Token::Symbol("[") => return Ok(Command::Bra),
+            Token::Symbol("]") => return Ok(Command::Ket),
+            Token::Symbol("?") => return Ok(Command::True),
+            Token::Symbol("->") => return Ok(Command::SliceTo(self.string()?)),
+            Token::Symbol("=>") => return Ok(Command::AssignTo(self.string()?)),
+            Token::Symbol("<-") => return Ok(Command::SliceFrom(self.text()?)),
+            Token::Symbol("<+") => return Ok(Command::Insert(self.text()?)),
+            Token::Symbol("$") => return self.integer_command(),
+            Token::Name(name) => name,
+            token => return Err(format!("unexpected {}", token.describe())),
+        };
+        Ok(match name.as_str() {
+            "not" => Command::Not(self.boxed()?),
+            "test" => Command::Test(self.boxed()?),
+            "try" => Command::Try(self.boxed()?),
+            "do" => Command::Do(self.boxed()?),
+            "fail" => Command::Fail(self.boxed()?),
+            "goto" => Command::Goto(self.boxed()?),
+            "gopast" => Command::Gopast(self.boxed()?),
+            "repeat" => Command::Repeat(self.boxed()?),
+            "backwards" => Command::Backwards(self.boxed()?),
+            "loop" => Command::Loop(self.expr()?, self.boxed()?),
+            "atleast" => Command::Atleast(self.expr()?, self.boxed()?),
+            "setlimit" => {
+                let limit = self.boxed()?;
+                match self.next()? {
+                    token if token.is_name("for") => {}
+                    token => return Err(format!("expected 'for', found {}", token.describe())),
+                }
+                Command::Setlimit(limit, self.boxed()?)
+            }
+            "hop" => Command::Hop(self.expr()?),
+            "next" => Command::Next,
+            "setmark" => Command::Setmark(self.integer()?),
+            "tomark" => Command::Tomark(self.expr()?),
+            "atmark" => Command::Atmark(self.expr()?),
+            "tolimit" => Command::Tolimit,
+            "atlimit" => Command::Atlimit,
+            "true" => Command::True,
+            "false" => Command::False,
+            "delete" => Command::Delete,
+            "insert" => Command::Insert(self.text()?),
+            "attach" => Command::Attach(self.text()?),
+            "set" => Command::Set(self.boolean()?),
+            "unset" => Command::Unset(self.boolean()?),
+            "non" => {
+                if self.peek()?.is_symbol("-") {
+                    self.next()?;
+                }
+                Command::Grouping {
+                    grouping: self.grouping()?,
+                    negated: true,
+                }
+            }
+            "substring" => {
+                if self.substring.is_some() {
+                    return Err("two 'substring's before an 'among'".to_owned());
+                }
+                let among = self.reserve_among();
+                self.substring = Some(among);
+                Command::Substring(among)
+            }
+            "among" => self.among()?,
+            "reverse" => return Err("'reverse' is not supported".to_owned()),
+            _ => match self.names.get(&name) {
+                Some(&Name::Routine(index)) => Command::Call(index),
+                Some(&Name::Grouping(index)) => Command::Grouping {
+                    grouping: index,
+                    negated: false,
+                },
+                Some(&Name::Boolean(index)) => Command::BoolTest(index),
+                Some(&Name::String(index)) => Command::StringVar(index),
+                Some(Name::Integer(_)) => {
+                    return Err(format!("integer '{}' used as a command", name))
+                }
+                None => return Err(format!("'{}' is not declared", name)),
+            },
+        })
+    }
+
+    fn reserve_among(&mut self) -> usize {
+        self.amongs.push(Among {
+            entries: Vec::new(),
+            starter: None,
+            actions: Vec::new(),
+        });
+        self.amongs.len() - 1
+    }
+
+    fn among(&mut self) -> Result<Command, String> {
+        // Taken before the actions are read, which may hold their own
+        // `substring ... among` pairs
+        let matched = self.substring.take();
+        let index = matched.unwrap_or_else(|| self.reserve_among());
+        self.expect_symbol("(")?;
+
+        // An action before the first string is the among's starter
+        let starter = if self.peek()?.is_symbol("(") {
+            self.next()?;
+            Some(self.list()?)
+        } else {
+            None
+        };
+        let mut entries = Vec::new();
+        let mut actions = Vec::new();
+        let mut pending: Vec<(Vec<char>, Option<usize>)> = Vec::new();
+        loop {
+            match self.next()? {
+                token if token.is_symbol(")") => break,
+                Token::Literal(text) => {
+                    let condition = match self.peek()?.clone() {
+                        Token::Name(name) => match self.names.get(&name) {
+                            Some(&Name::Routine(routine)) => {
+                                self.next()?;
+                                Some(routine)
+                            }
+                            _ => {
+                                return Err(format!("'{}' in among is not a routine", name));
+                            }
+                        },
+                        _ => None,
+                    };
+                    pending.push((text, condition));
+                }
+                token if token.is_symbol("(") => {
+                    if pending.is_empty() {
+                        return Err("among action without strings".to_owned());
+                    }
+                    actions.push(self.list()?);
+                    let action = actions.len() - 1;
+                    entries.extend(pending.drain(..).map(|(text, condition)| AmongEntry {
+                        text,
+                        condition,
+                        action,
+                    }));
+                }
+                token => {
+                    return Err(format!(
+                        "expected a string or action in among, found {}",
+                        token.describe()
+                    ))
+                }
+            }
+        }
+        if !pending.is_empty() {
+            actions.push(Command::Seq(Vec::new()));
+            let action = actions.len() - 1;
+            entries.extend(pending.drain(..).map(|(text, condition)| AmongEntry {
+                text,
+                condition,
+                action,
+            }));
+        }
+        // As in the Snowball compiler, a repeated string would never match
+        for (i, entry) in entries.iter().enumerate() {
+            if entries[..i]
+                .iter()
+                .any(|other| other.text == entry.text && other.condition == entry.condition)
+            {
+                return Err(format!(
+                    "'{}' appears twice in among",
+                    entry.text.iter().collect::<String>()
+                ));
+            }
+        }
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.text.len()));
+
+        self.amongs[index] = Among {
+            entries,
+            starter,
+            actions,
+        };
+        Ok(Command::Among {
+            among: index,
+            matched: matched.is_some(),
+        })
+    }
+
+    // After `$`: an assignment or test of one integer, `$(AE relop AE)`, or
+    // a command run on a string variable
+    fn integer_command(&mut self) -> Result<Command, String> {
+        let string = match self.peek()?.clone() {
+            Token::Name(name) => match self.names.get(&name) {
+                Some(&Name::String(index)) => Some(index),
+                _ => None,
+            },
+            _ => None,
+        };
+        if let Some(index) = string {
+            self.next()?;
+            return Ok(Command::OnString(index, self.boxed()?));
+        }
+        if self.peek()?.is_symbol("(") {
+            self.next()?;
+            let left = self.expr()?;
+            let op = self.relop()?;
+            let right = self.expr()?;
+            self.expect_symbol(")")?;
+            return Ok(Command::IntTest(left, op, right));
+        }
+        let variable = self.integer()?;
+        let op = match self.peek()? {
+            Token::Symbol("=") => AssignOp::Set,
+            Token::Symbol("+=") => AssignOp::Add,
+            Token::Symbol("-=") => AssignOp::Sub,
+            Token::Symbol("*=") => AssignOp::Mul,
+            Token::Symbol("/=") => AssignOp::Div,
+            _ => {
+                let op = self.relop()?;
+                return Ok(Command::IntTest(Expr::Int(variable), op, self.expr()?));
+            }
+        };
+        self.next()?;
+        Ok(Command::IntAssign(variable, op, self.expr()?))
+    }
+
+    fn relop(&mut self) -> Result<RelOp, String> {
+        Ok(match self.next()? {
+            Token::Symbol("==") => RelOp::Eq,
+            Token::Symbol("!=") => RelOp::Ne,
+            Token::Symbol("<") => RelOp::Lt,
+            Token::Symbol("<=") => RelOp::Le,
+            Token::Symbol(">") => RelOp::Gt,
+            Token::Symbol(">=") => RelOp::Ge,
+            token => return Err(format!("expected a comparison, found {}", token.describe())),
+        })
+    }
+
+    // Arithmetic expression: terms joined by + and -
+    fn expr(&mut self) -> Result<Expr, String> {
+        let mut expr = self.term()?;
+        loop {
+            let op = match self.peek()? {
+                token if token.is_symbol("+") => BinOp::Add,
+                token if token.is_symbol("-") => BinOp::Sub,
+                _ => return Ok(expr),
+            };
+            self.next()?;
+            expr = Expr::Binary(Box::new(expr), op, Box::new(self.term()?));
+        }
+    }
+
+    fn term(&mut self) -> Result<Expr, String> {
+        let mut expr = self.factor()?;
+        loop {
+            let op = match self.peek()? {
+                token if token.is_symbol("*") => BinOp::Mul,
+                token if token.is_symbol("/") => BinOp::Div,
+                _ => return Ok(expr),
+            };
+            self.next()?;
+            expr = Expr::Binary(Box::new(expr), op, Box::new(self.factor()?));
+        }
+    }
+
+    fn factor(&mut self) -> Result<Expr, String> {
+        let name = match self.next()? {
+            Token::Number(number) => return Ok(Expr::Number(number)),
+            Token::Symbol("-") => return Ok(Expr::Neg(Box::new(self.factor()?))),
+            Token::Symbol("(") => {
+                let expr = self.expr()?;
+                self.expect_symbol(")")?;
+                return Ok(expr);
+            }
+            Token::Name(name) => name,
+            token => {
+                return Err(format!(
+                    "expected an arithmetic expression, found {}",
+                    token.describe()
+                ))
+            }
+        };
+        Ok(match name.as_str() {
+            "cursor" => Expr::Cursor,
+            "limit" => Expr::Limit,
+            // Lengths are counted in characters
+            "size" | "len" => Expr::Size,
+            "sizeof" | "lenof" => Expr::SizeOf(self.string()?),
+            "maxint" => Expr::Number(i64::from(i32::MAX)),
+            "minint" => Expr::Number(i64::from(i32::MIN)),
+            _ => match self.names.get(&name) {
+                Some(&Name::Integer(index)) => Expr::Int(index),
+                _ => return Err(format!("'{}' is not an integer", name)),
+            },
+        })
+    }
+}
+
+fn post_increment(counter: &mut usize) -> usize {
+    *counter += 1;
+    *counter - 1
+}
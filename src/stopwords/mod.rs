@@ -0,0 +1,177 @@
+// Stopword filtering for `SnowballStemmer(stopwords=...)` and
+// `filter_stopwords`. The Snowball project's lists are embedded for the
+// languages it publishes one for, and lists of common function words for the
+// others. Users can add words to the built-in list or replace it.
+
+use crate::algorithm_to_u8;
+use crate::algorithms::Algorithm;
+use crate::normalize::Normalizer;
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyString;
+use std::collections::HashSet;
+use std::sync::{Arc, OnceLock};
+
+fn source(algorithm: Algorithm) -> Option<&'static str> {
+    Some(match algorithm {
+        Algorithm::Arabic => include_str!("arabic.txt"),
+        Algorithm::Danish => include_str!("danish.txt"),
+        Algorithm::Dutch => include_str!("dutch.txt"),
+        Algorithm::English => include_str!("english.txt"),
+        Algorithm::Finnish => include_str!("finnish.txt"),
+        Algorithm::French => include_str!("french.txt"),
+        Algorithm::German => include_str!("german.txt"),
+        Algorithm::Greek => include_str!("greek.txt"),
+        Algorithm::Hindi => include_str!("hindi.txt"),
+        Algorithm::Hungarian => include_str!("hungarian.txt"),
+        Algorithm::Indonesian => include_str!("indonesian.txt"),
+        Algorithm::Italian => include_str!("italian.txt"),
+        Algorithm::Norwegian => include_str!("norwegian.txt"),
+        Algorithm::Portuguese => include_str!("portuguese.txt"),
+        Algorithm::Romanian => include_str!("romanian.txt"),
+        Algorithm::Russian => include_str!("russian.txt"),
+        Algorithm::Spanish => include_str!("spanish.txt"),
+        Algorithm::Swedish => include_str!("swedish.txt"),
+        Algorithm::Tamil => include_str!("tamil.txt"),
+        Algorithm::Turkish => include_str!("turkish.txt"),
+        Algorithm::Custom(_) => return None,
+    })
+}
+
+pub fn has_builtin(algorithm: Algorithm) -> bool {
+    source(algorithm).is_some()
+}
+
+// The built-in list of `algorithm`, parsed on first use. One word per line;
+// lines starting with '#' are comments.
+fn builtin(algorithm: Algorithm) -> Option<&'static HashSet<&'static str>> {
+    static LISTS: [OnceLock<HashSet<&'static str>>; 20] = [const { OnceLock::new() }; 20];
+    let text = source(algorithm)?;
+    let list = &LISTS[algorithm_to_u8(algorithm) as usize];
+    Some(list.get_or_init(|| {
+        text.lines()
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .collect()
+    }))
+}
+
+// The `stopwords` constructor argument: whether to filter with the built-in
+// list, or a list to filter with instead
+#[derive(FromPyObject)]
+pub enum StopwordsArg<'py> {
+    Enabled(bool),
+    Words(Bound<'py, PyAny>),
+}
+
+// Words from any iterable of strings. A single string is rejected rather than
+// read as a list of characters.
+pub fn extract_words(words: &Bound<'_, PyAny>) -> PyResult<HashSet<String>> {
+    if words.is_instance_of::<PyString>() {
+        return Err(PyTypeError::new_err(
+            "Stopwords must be an iterable of strings, not a string",
+        ));
+    }
+    words.try_iter()?.map(|word| word?.extract()).collect()
+}
+
+#[derive(Clone, Default, PartialEq)]
+pub struct Stopwords {
+    // Whether the stemming methods drop stopwords from their results
+    pub filter: bool,
+    builtin: Option<&'static HashSet<&'static str>>,
+    // A user list used instead of the built-in one
+    replacement: Option<Arc<HashSet<String>>>,
+    // User words added to the built-in or replacement list
+    extra: Option<Arc<HashSet<String>>>,
+}
+
+impl Stopwords {
+    pub fn new(
+        algorithm: Algorithm,
+        stopwords: StopwordsArg<'_>,
+        extra: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<Self> {
+        let (filter, replacement) = match stopwords {
+            StopwordsArg::Enabled(filter) => (filter, None),
+            StopwordsArg::Words(words) => (true, Some(Arc::new(extract_words(&words)?))),
+        };
+        let extra = extra.map(extract_words).transpose()?.map(Arc::new);
+        if filter && replacement.is_none() && !has_builtin(algorithm) {
+            return Err(PyValueError::new_err(
+                "There is no built-in stopword list for this stemmer; pass a list of stopwords",
+            ));
+        }
+        Ok(Stopwords {
+            filter,
+            builtin: builtin(algorithm),
+            replacement,
+            extra,
+        })
+    }
+
+    fn contains(&self, word: &str) -> bool {
+        let listed = match &self.replacement {
+            Some(replacement) => replacement.contains(word),
+            None => self.builtin.is_some_and(|builtin| builtin.contains(word)),
+        };
+        listed
+            || self
+                .extra
+                .as_ref()
+                .is_some_and(|extra| extra.contains(word))
+    }
+
+    // Whether `word` is a stopword as given, as `normalizer` would stem it,
+    // or lowercased. The lists are lowercase and keep their accents.
+    pub fn matches(&self, normalizer: Normalizer, word: &str) -> bool {
+        if self.contains(word) {
+            return true;
+        }
+        let normalized = normalizer.apply(word);
+        if *normalized != *word && self.contains(&normalized) {
+            return true;
+        }
+        word.chars().any(char::is_uppercase) && self.contains(&word.to_lowercase())
+    }
+
+    // Every word in the list
+    pub fn words(&self) -> HashSet<String> {
+        let mut words: HashSet<String> = match &self.replacement {
+            Some(replacement) => replacement.iter().cloned().collect(),
+            None => self
+                .builtin
+                .into_iter()
+                .flatten()
+                .map(|word| word.to_string())
+                .collect(),
+        };
+        words.extend(self.extra.iter().flat_map(|extra| extra.iter().cloned()));
+        words
+    }
+
+    // The constructor arguments that recreate these stopwords, with user
+    // lists sorted so that they pickle and print the same way every time
+    pub fn replacement(&self) -> Option<Vec<String>> {
+        self.replacement.as_deref().map(sorted)
+    }
+
+    pub fn extra(&self) -> Option<Vec<String>> {
+        self.extra.as_deref().map(sorted)
+    }
+
+    pub fn replacement_len(&self) -> Option<usize> {
+        self.replacement
+            .as_ref()
+            .map(|replacement| replacement.len())
+    }
+
+    pub fn extra_len(&self) -> Option<usize> {
+        self.extra.as_ref().map(|extra| extra.len())
+    }
+}
+
+fn sorted(words: &HashSet<String>) -> Vec<String> {
+    let mut words: Vec<String> = words.iter().cloned().collect();
+    words.sort_unstable();
+    words
+}
@@ -0,0 +1,358 @@
+// Text segmentation for `stem_text`: UAX #29 word boundaries, so callers
+// don't each split text their own way in Python. Punctuation, whitespace and
+// symbols between words are dropped, unless a stemmer's `TokenPolicy` covers
+// them (URLs, emails, mentions, hashtags, emoji).
+
+use crate::classify::{TokenClass, TokenPolicy};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyTuple;
+use std::borrow::Cow;
+use unicode_segmentation::{UWordBoundIndices, UnicodeSegmentation, UnicodeWordIndices};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TokenKind {
+    Word,
+    // No letters at all, such as "2024" or "3.14"; never stemmed
+    Number,
+    // Recognized because the stemmer has a `TokenPolicy` for its class
+    Special(TokenClass),
+}
+
+pub struct Token<'a> {
+    pub text: Cow<'a, str>,
+    pub kind: TokenKind,
+    // The token as it appears in the input, starting at byte `start`
+    pub surface: &'a str,
+    pub start: usize,
+}
+
+#[pyclass(module = "py_rust_stemmers_tuned", frozen)]
+pub struct Tokenizer {
+    lowercase: bool,
+    // Bounds on token length in characters; tokens outside them are dropped
+    min_length: usize,
+    max_length: Option<usize>,
+    numbers: bool,
+}
+
+// Used when `stem_text` is not given a tokenizer
+pub static DEFAULT_TOKENIZER: Tokenizer = Tokenizer {
+    lowercase: true,
+    min_length: 1,
+    max_length: None,
+    numbers: true,
+};
+
+impl Tokenizer {
+    pub fn tokens<'a>(&'a self, text: &'a str) -> Tokens<'a> {
+        self.tokens_with(text, &NO_POLICY)
+    }
+
+    // Also recognizes the classes of token that `policy` has a policy for.
+    // URLs, emails, mentions and hashtags span several word segments, so
+    // they are matched on whitespace-separated chunks.
+    pub fn tokens_with<'a>(&'a self, text: &'a str, policy: &'a TokenPolicy) -> Tokens<'a> {
+        let segments = if policy.is_empty() {
+            Segments::Words(text.unicode_word_indices())
+        } else {
+            Segments::Bounds(text.split_word_bound_indices())
+        };
+        Tokens {
+            tokenizer: self,
+            policy,
+            text,
+            segments,
+            resume: 0,
+        }
+    }
+
+    fn fits(&self, token: &str) -> bool {
+        let length = token.chars().count();
+        length >= self.min_length && self.max_length.is_none_or(|max| length <= max)
+    }
+
+    fn word<'a>(&self, start: usize, word: &'a str) -> Option<Token<'a>> {
+        if !self.fits(word) {
+            return None;
+        }
+        if !word.chars().any(char::is_alphabetic) {
+            return self.numbers.then_some(Token {
+                text: Cow::Borrowed(word),
+                kind: TokenKind::Number,
+                surface: word,
+                start,
+            });
+        }
+        Some(Token {
+            text: self.lowercased(word),
+            kind: TokenKind::Word,
+            surface: word,
+            start,
+        })
+    }
+
+    fn special<'a>(&self, class: TokenClass, start: usize, token: &'a str) -> Option<Token<'a>> {
+        if !self.fits(token) {
+            return None;
+        }
+        // `numbers=False` drops every token without letters except emoji
+        let letters = token.chars().any(char::is_alphabetic);
+        if !letters && !self.numbers && class != TokenClass::Emoji {
+            return None;
+        }
+        // URL paths are case-sensitive
+        let text = match class {
+            TokenClass::Url => Cow::Borrowed(token),
+            _ => self.lowercased(token),
+        };
+        Some(Token {
+            text,
+            kind: TokenKind::Special(class),
+            surface: token,
+            start,
+        })
+    }
+
+    // The stemmers expect lowercase input
+    fn lowercased<'a>(&self, word: &'a str) -> Cow<'a, str> {
+        if self.lowercase && word.chars().any(char::is_uppercase) {
+            Cow::Owned(word.to_lowercase())
+        } else {
+            Cow::Borrowed(word)
+        }
+    }
+}
+
+static NO_POLICY: TokenPolicy = TokenPolicy::NONE;
+
+enum Segments<'a> {
+    // Only the segments that contain letters or digits
+    Words(UnicodeWordIndices<'a>),
+    // Every segment, including punctuation and emoji
+    Bounds(UWordBoundIndices<'a>),
+}
+
+pub struct Tokens<'a> {
+    tokenizer: &'a Tokenizer,
+    policy: &'a TokenPolicy,
+    text: &'a str,
+    segments: Segments<'a>,
+    // Segments before this byte belong to a token already returned
+    resume: usize,
+}
+
+impl<'a> Tokens<'a> {
+    // A URL, email, mention or hashtag starting at byte `start`, which must
+    // begin a chunk: trailing punctuation is not part of it
+    fn chunk(&self, start: usize) -> Option<(TokenClass, &'a str)> {
+        if !self.policy.has_chunk_classes() {
+            return None;
+        }
+        let before = self.text[..start].chars().next_back();
+        if before.is_some_and(|c| !c.is_whitespace() && !"([{<\"'«“‘".contains(c)) {
+            return None;
+        }
+        let chunk = self.text[start..].split(char::is_whitespace).next()?;
+        let chunk = chunk.trim_end_matches([
+            '.', ',', ';', ':', '!', '?', ')', ']', '}', '>', '"', '\'', '»', '”', '’', '…',
+        ]);
+        self.policy
+            .classify_chunk(chunk)
+            .map(|class| (class, chunk))
+    }
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        loop {
+            let (start, segment) = match &mut self.segments {
+                Segments::Words(words) => {
+                    let (start, word) = words.next()?;
+                    match self.tokenizer.word(start, word) {
+                        Some(token) => return Some(token),
+                        None => continue,
+                    }
+                }
+                Segments::Bounds(bounds) => bounds.next()?,
+            };
+            if start < self.resume {
+                continue;
+            }
+            if let Some((class, chunk)) = self.chunk(start) {
+                self.resume = start + chunk.len();
+                match self.tokenizer.special(class, start, chunk) {
+                    Some(token) => return Some(token),
+                    None => continue,
+                }
+            }
+            let token = match self.policy.classify(segment) {
+                Some(class) => self.tokenizer.special(class, start, segment),
+                None if segment.chars().any(char::is_alphanumeric) => {
+                    self.tokenizer.word(start, segment)
+                }
+                None => None,
+            };
+            if token.is_some() {
+                return token;
+            }
+        }
+    }
+}
+
+// Turns increasing byte offsets into `text` into char and UTF-16 offsets,
+// counting each character once however many tokens there are
+pub struct OffsetCounter<'a> {
+    text: &'a str,
+    byte: usize,
+    chars: usize,
+    utf16: usize,
+}
+
+impl<'a> OffsetCounter<'a> {
+    pub fn new(text: &'a str) -> Self {
+        OffsetCounter {
+            text,
+            byte: 0,
+            chars: 0,
+            utf16: 0,
+        }
+    }
+
+    // The (char, UTF-16) offsets of `byte`, which must not be smaller than
+    // the previous one
+    pub fn advance(&mut self, byte: usize) -> (usize, usize) {
+        for c in self.text[self.byte..byte].chars() {
+            self.chars += 1;
+            self.utf16 += c.len_utf16();
+        }
+        self.byte = byte;
+        (self.chars, self.utf16)
+    }
+}
+
+// A stemmed token and where it came from, for highlighting matches in the
+// original text. Offsets are [start, end) in characters (Python string
+// indices), in bytes of the UTF-8 text and in UTF-16 code units (JavaScript).
+#[pyclass(module = "py_rust_stemmers_tuned", frozen, get_all)]
+pub struct StemmedToken {
+    pub stem: String,
+    pub surface: String,
+    pub start: usize,
+    pub end: usize,
+    pub byte_start: usize,
+    pub byte_end: usize,
+    pub utf16_start: usize,
+    pub utf16_end: usize,
+}
+
+impl StemmedToken {
+    pub fn new(token: &Token<'_>, stem: String, offsets: &mut OffsetCounter<'_>) -> Self {
+        let byte_end = token.start + token.surface.len();
+        let (start, utf16_start) = offsets.advance(token.start);
+        let (end, utf16_end) = offsets.advance(byte_end);
+        StemmedToken {
+            stem,
+            surface: token.surface.to_owned(),
+            start,
+            end,
+            byte_start: token.start,
+            byte_end,
+            utf16_start,
+            utf16_end,
+        }
+    }
+}
+
+#[pymethods]
+impl StemmedToken {
+    fn __repr__(&self) -> String {
+        format!(
+            "StemmedToken(stem={:?}, surface={:?}, start={}, end={})",
+            self.stem, self.surface, self.start, self.end
+        )
+    }
+}
+
+#[pymethods]
+impl Tokenizer {
+    #[new]
+    #[pyo3(signature = (lowercase = true, min_length = 1, max_length = None, numbers = true))]
+    fn new(
+        lowercase: bool,
+        min_length: usize,
+        max_length: Option<usize>,
+        numbers: bool,
+    ) -> PyResult<Self> {
+        if max_length.is_some_and(|max| max < min_length) {
+            return Err(PyValueError::new_err(
+                "max_length must not be smaller than min_length",
+            ));
+        }
+        Ok(Tokenizer {
+            lowercase,
+            min_length,
+            max_length,
+            numbers,
+        })
+    }
+
+    #[getter]
+    fn lowercase(&self) -> bool {
+        self.lowercase
+    }
+
+    #[getter]
+    fn min_length(&self) -> usize {
+        self.min_length
+    }
+
+    #[getter]
+    fn max_length(&self) -> Option<usize> {
+        self.max_length
+    }
+
+    #[getter]
+    fn numbers(&self) -> bool {
+        self.numbers
+    }
+
+    // The tokens `stem_text` would stem, before stemming
+    fn tokenize(&self, py: Python<'_>, text: &str) -> Vec<String> {
+        py.detach(|| {
+            self.tokens(text)
+                .map(|token| token.text.into_owned())
+                .collect()
+        })
+    }
+
+    fn __reduce__<'py>(
+        &self,
+        py: Python<'py>,
+    ) -> PyResult<(Bound<'py, PyAny>, Bound<'py, PyTuple>)> {
+        let args = (
+            self.lowercase,
+            self.min_length,
+            self.max_length,
+            self.numbers,
+        );
+        Ok((
+            py.get_type::<Tokenizer>().into_any(),
+            args.into_pyobject(py)?,
+        ))
+    }
+
+    fn __repr__(&self) -> String {
+        let bool_repr = |value: bool| if value { "True" } else { "False" };
+        format!(
+            "Tokenizer(lowercase={}, min_length={}, max_length={}, numbers={})",
+            bool_repr(self.lowercase),
+            self.min_length,
+            self.max_length
+                .map_or_else(|| "None".to_owned(), |max| max.to_string()),
+            bool_repr(self.numbers)
+        )
+    }
+}
@@ -0,0 +1,120 @@
+// Cache warm-up: stem a known vocabulary ahead of time so the first real
+// requests are already cache hits.
+
+use crate::cache::StemCache;
+use crate::SnowballStemmer;
+use pyo3::exceptions::PyRuntimeError;
+use pyo3::prelude::*;
+use rayon::prelude::*;
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+
+// Stem `words` in parallel and store the results, returning how many were
+// stemmed. The vocabulary is explicit, so it bypasses admission policies.
+// Words go through the stemmer's stopwords, token policy, normalization and
+// overrides, so only entries its lookups can hit are stored.
+pub fn warm(stemmer: &SnowballStemmer, cache: &StemCache, words: &[String]) -> usize {
+    words
+        .par_iter()
+        .with_min_len(250)
+        .filter(|word| stemmer.warm_word(cache, word))
+        .count()
+}
+
+// One word per line; anything after the first whitespace (e.g. a frequency
+// column) is ignored, as are blank lines
+pub fn read_vocabulary(path: &Path) -> PyResult<Vec<String>> {
+    Ok(fs::read_to_string(path)?
+        .lines()
+        .filter_map(|line| line.split_whitespace().next())
+        .map(str::to_owned)
+        .collect())
+}
+
+// Foreground warm-ups return the word count, background ones a handle
+#[derive(IntoPyObject)]
+pub enum Warmup {
+    Done(usize),
+    Background(CacheWarmup),
+}
+
+#[derive(Default)]
+struct WarmupResult {
+    // Only ever locked briefly, so it is safe to take while holding the GIL
+    result: Mutex<Option<PyResult<usize>>>,
+    finished: Condvar,
+}
+
+// Handle to a warm-up running on a background thread
+#[pyclass(module = "py_rust_stemmers_tuned", frozen)]
+pub struct CacheWarmup {
+    shared: Arc<WarmupResult>,
+}
+
+impl CacheWarmup {
+    // Run `task` on a new thread, with the GIL released while it works.
+    // Release builds abort on panic (`panic = 'abort'`), so a panic in a
+    // warm-up ends the process there; only builds that unwind report it as a
+    // `RuntimeError` from `wait`.
+    pub fn spawn(
+        stemmer: Py<SnowballStemmer>,
+        task: impl FnOnce(&SnowballStemmer) -> PyResult<usize> + Send + 'static,
+    ) -> Self {
+        let shared = Arc::new(WarmupResult::default());
+        let publish = shared.clone();
+        std::thread::spawn(move || {
+            let run = || {
+                Python::attach(|py| {
+                    let stemmer = stemmer.borrow(py);
+                    let stemmer: &SnowballStemmer = &stemmer;
+                    py.detach(|| task(stemmer))
+                })
+            };
+            let result = panic::catch_unwind(AssertUnwindSafe(run))
+                .unwrap_or_else(|_| Err(PyRuntimeError::new_err("cache warm-up panicked")));
+            *publish
+                .result
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner) = Some(result);
+            publish.finished.notify_all();
+        });
+        CacheWarmup { shared }
+    }
+
+    fn result(&self) -> MutexGuard<'_, Option<PyResult<usize>>> {
+        self.shared
+            .result
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[pymethods]
+impl CacheWarmup {
+    #[getter]
+    fn done(&self) -> bool {
+        self.result().is_some()
+    }
+
+    // Block until the warm-up finishes and return the number of words stemmed.
+    // Errors raised by the warm-up (e.g. an unreadable file) are re-raised here.
+    fn wait(&self, py: Python<'_>) -> PyResult<usize> {
+        py.detach(|| {
+            let mut result = self.result();
+            while result.is_none() {
+                result = self
+                    .shared
+                    .finished
+                    .wait(result)
+                    .unwrap_or_else(PoisonError::into_inner);
+            }
+        });
+        match self.result().as_ref() {
+            Some(Ok(count)) => Ok(*count),
+            Some(Err(err)) => Err(err.clone_ref(py)),
+            None => unreachable!("warm-up result is set before waiters are woken"),
+        }
+    }
+}
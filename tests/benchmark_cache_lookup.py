@@ -0,0 +1,34 @@
+"""
+Benchmark for the cache hit path.
+
+Every word is stemmed once to populate the cache, then the timed runs only
+hit the cache. Compare the numbers between builds to see the cost of a cache
+lookup, and against cache=False to see what the cache saves. The gain of
+borrowed keys and foldhash over owned keys and SipHash is measured on its own
+by `cargo bench --bench cache_lookup`.
+"""
+import time
+from py_rust_stemmers import SnowballStemmer
+
+text = """This stem form is often a word itself, but this is not always the case as this is not a requirement for text search systems, which are the intended field of use. We also aim to conflate words with the same meaning, rather than all words with a common linguistic root (so awe and awful don't have the same stem), and over-stemming is more problematic than under-stemming so we tend not to stem in cases that are hard to resolve. If you want to always reduce words to a root form and/or get a root form which is itself a word then Snowball's stemming algorithms likely aren't the right answer."""
+words = text.split()
+batch = words * 100
+rounds = 200
+
+
+def bench(name, func, total_words):
+    start = time.perf_counter()
+    for _ in range(rounds):
+        func()
+    elapsed = time.perf_counter() - start
+    print(f"{name:<40} {elapsed * 1e9 / (rounds * total_words):8.1f} ns/word")
+
+
+for cache in (True, False):
+    s = SnowballStemmer("english", cache=cache)
+    s.stem_words(batch)  # populate the cache
+    label = "cached" if cache else "uncached"
+
+    bench(f"stem_word ({label})", lambda: [s.stem_word(w) for w in words], len(words))
+    bench(f"stem_words ({label})", lambda: s.stem_words(batch), len(batch))
+    bench(f"stem_words_parallel ({label})", lambda: s.stem_words_parallel(batch), len(batch))
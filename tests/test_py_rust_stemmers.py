@@ -1,5 +1,25 @@
+import os
+import pickle
+import tempfile
 import unittest
-from py_rust_stemmers import SnowballStemmer
+from py_rust_stemmers import (
+    AutoStemmer,
+    MultiLanguageStemmer,
+    SnowballStemmer,
+    StemCache,
+    StemRecorder,
+    TokenPolicy,
+    Tokenizer,
+    cache_stats,
+    clear_cache,
+    detect_language,
+    get_cache_limits,
+    load_cache,
+    save_cache,
+    set_cache_limits,
+    shrink_cache,
+    supported_languages,
+)
 
 class TestRustStemmer(unittest.TestCase):
       
@@ -17,6 +37,24 @@ class TestRustStemmer(unittest.TestCase):
         result = [s.stem_word(w) for w in words]
         self.assertEqual(result, expected)
 
+    def test_indonesian_stemming(self):
+        s = SnowballStemmer('indonesian')
+        words = ["bersembunyi", "pembangunan", "menyapu", "bukunya", "pelajaran", "dimakan"]
+        expected = ["sembunyi", "bangun", "sapu", "buku", "ajar", "makan"]
+        self.assertEqual(s.stem_words(words), expected)
+        self.assertEqual(s.stem_words_parallel(words * 200), expected * 200)
+        # Cases the upstream algorithm documents, including its amended -kan rule
+        words = ["perbaikan", "peledakan", "bekerja", "memukul", "pemukul", "kebersamaan", "siapapun", "pertanian"]
+        expected = ["baik", "ledak", "kerja", "pukul", "pukul", "sama", "siapa", "tani"]
+        self.assertEqual(s.stem_words(words), expected)
+
+    def test_hindi_stemming(self):
+        s = SnowballStemmer('hindi')
+        words = ["लड़कियाँ", "लड़कियों", "किताबें", "घरों", "पढ़ता", "क"]
+        expected = ["लड़क", "लड़क", "किताब", "घर", "पढ़", "क"]
+        self.assertEqual([s.stem_word(w) for w in words], expected)
+        self.assertEqual(s.stem_words_parallel(words * 200), expected * 200)
+
     def test_empty_input(self):
         s = SnowballStemmer('english')
         expected = ['']
@@ -28,6 +66,535 @@ class TestRustStemmer(unittest.TestCase):
         with self.assertRaises(ValueError):
             s = SnowballStemmer('invalid_lang')
 
+    def test_language_codes_and_aliases(self):
+        """Test that ISO codes, aliases and locale tags resolve to a language"""
+        cases = {
+            'en': 'english', 'ENG': 'english', 'porter2': 'english', 'en_US': 'english',
+            'de': 'german', 'ger': 'german', 'deu': 'german', 'de-AT': 'german',
+            'pt-BR': 'portuguese', 'nb': 'norwegian', 'nob': 'norwegian',
+            'Norwegian Bokmål': 'norwegian', 'no': 'norwegian', 'el': 'greek',
+            ' Spanish ': 'spanish', 'pt-Latn-BR': 'portuguese',
+        }
+        for lang, expected in cases.items():
+            self.assertEqual(SnowballStemmer(lang).language, expected, lang)
+        self.assertEqual(SnowballStemmer('es').stem_word("felicidad"), "felic")
+        for lang in ("xx", "zh-Hant", "xx-en", ""):
+            with self.assertRaises(ValueError):
+                SnowballStemmer(lang)
+
+    def test_supported_languages(self):
+        """Test that every listed language can be used by name and by code"""
+        languages = supported_languages()
+        self.assertEqual(len(languages), 20)
+        names = [info.name for info in languages]
+        self.assertEqual(names, sorted(names))
+        for info in languages:
+            self.assertEqual(SnowballStemmer(info.name).language, info.name)
+            self.assertEqual(SnowballStemmer(info.iso639_1).language, info.name)
+            self.assertEqual(SnowballStemmer(info.iso639_3).language, info.name)
+            for alias in info.aliases:
+                self.assertEqual(SnowballStemmer(alias).language, info.name)
+        english = languages[names.index('english')]
+        self.assertEqual((english.iso639_1, english.iso639_3), ('en', 'eng'))
+        self.assertEqual(english.script, 'Latin')
+        self.assertTrue(english.has_exceptions)
+        self.assertEqual(languages[names.index('russian')].script, 'Cyrillic')
+
+    def test_classes_name_their_module(self):
+        """Test that every class reports the extension module it is defined in"""
+        import py_rust_stemmers
+        classes = [obj for obj in vars(py_rust_stemmers).values() if isinstance(obj, type)]
+        self.assertEqual(len(classes), 12)
+        for cls in classes:
+            self.assertEqual(cls.__module__, "py_rust_stemmers_tuned", cls.__name__)
+        self.assertEqual(type(supported_languages()[0]).__module__, "py_rust_stemmers_tuned")
+        self.assertEqual(type(detect_language("text")).__module__, "py_rust_stemmers_tuned")
+
+    def test_detect_language(self):
+        """Test detection, restricted to supported languages, with a fallback"""
+        cases = {
+            "The quick brown fox jumps over the lazy dog and runs away": "english",
+            "Говорят, что в Москве очень холодно зимой": "russian",
+            "Saya sedang membaca buku di perpustakaan bersama teman": "indonesian",
+            "Hij fietst elke dag naar zijn werk in de stad": "dutch",
+        }
+        for text, expected in cases.items():
+            detection = detect_language(text)
+            self.assertEqual(detection.language, expected)
+            self.assertFalse(detection.is_fallback)
+            self.assertGreater(detection.confidence, 0.15)
+        tokens = detect_language("Hij fietst elke dag naar zijn werk".split())
+        self.assertEqual(tokens.language, "dutch")
+
+        for text in ("", "ok"):
+            detection = detect_language(text, default="fr")
+            self.assertEqual(detection.language, "french")
+            self.assertTrue(detection.is_fallback)
+        strict = detect_language(list(cases)[0], min_confidence=1.0, default="de")
+        self.assertEqual((strict.language, strict.is_fallback), ("german", True))
+        with self.assertRaises(ValueError):
+            detect_language("text", min_confidence=1.5)
+        with self.assertRaises(ValueError):
+            detect_language("text", default="klingon")
+
+    def test_auto_stemmer(self):
+        """Test that AutoStemmer stems each document with its detected language"""
+        cache = StemCache()
+        auto = AutoStemmer(default="spanish", cache=cache)
+        self.assertEqual(auto.default, "spanish")
+
+        stems, detection = auto.stem_words("running dogs are happily chasing cats around the houses".split())
+        self.assertEqual(detection.language, "english")
+        self.assertEqual(stems[:4], ["run", "dog", "are", "happili"])
+        stems, detection = auto.stem_words_parallel("les enfants jouaient dans les jardins".split())
+        self.assertEqual(detection.language, "french")
+        self.assertEqual(stems, ["le", "enfant", "jou", "dan", "le", "jardin"])
+        strict = AutoStemmer(default="spanish", min_confidence=1.0, cache=cache)
+        stems, detection = strict.stem_words("the lazy dog has felicidad".split())
+        self.assertTrue(detection.is_fallback)
+        self.assertEqual(stems[-1], "felic")
+
+        stemmer = auto.stemmer_for("Die Kinder spielten im Garten mit ihren Freunden")
+        self.assertEqual(stemmer.language, "german")
+        self.assertEqual(stemmer.cache, cache)
+        self.assertGreater(cache.stats().entries, 0)
+
+    def test_multi_language_stemmer(self):
+        """Test that tokens are routed to the algorithm for their script"""
+        cache = StemCache()
+        m = MultiLanguageStemmer("russian", {"latin": "en"}, cache=cache)
+        self.assertEqual(m.primary, "russian")
+        self.assertEqual(m.routes["Latin"], "english")
+        self.assertEqual(m.routes["Greek"], "greek")  # only Greek-script language
+        tokens = ["новые", "smartphones", "продаются", "μπαταρίες", "2024"]
+        expected = ["нов", "smartphon", "прода", "μπαταρι", "2024"]
+        self.assertEqual(m.stem_words(tokens), expected)
+        self.assertEqual(m.stem_words_parallel(tokens * 200), expected * 200)
+        self.assertEqual([m.language_for(t) for t in tokens], ["russian", "english", "russian", "greek", None])
+
+        # Entries are keyed by the routed algorithm, so they are shared
+        before = cache.stats().hits
+        self.assertEqual(SnowballStemmer("english", cache=cache).stem_word("smartphones"), "smartphon")
+        self.assertEqual(cache.stats().hits, before + 1)
+
+        # Without a Latin fallback, Latin tokens are left as is
+        m = MultiLanguageStemmer("russian", {"Greek": None}, cache=False)
+        self.assertNotIn("Latin", m.routes)
+        self.assertEqual(m.stem_words(["smartphones", "μπαταρίες", "новые"]), ["smartphones", "μπαταρίες", "нов"])
+        for fallbacks in ({"Cyrillic": "english"}, {"Latin": "russian"}, {"Klingon": "english"}):
+            with self.assertRaises(ValueError):
+                MultiLanguageStemmer("russian", fallbacks)
+
+    def test_snowball_source(self):
+        """Test that a Snowball program loaded at runtime stems through the cache"""
+        source = """
+            routines ( plural )
+            externals ( stem )
+            groupings ( v )
+            stringescapes {}
+            stringdef e' '{U+00E9}'
+            define v 'aeiouy'
+            backwardmode (
+                define plural as (
+                    [substring] among (
+                        'ies' (<- 'y')
+                        '{e'}s' (<- '{e'}')
+                        's' (test gopast v delete)
+                    )
+                )
+            )
+            define stem as backwards plural
+        """
+        cache = StemCache()
+        with tempfile.TemporaryDirectory() as tmp:
+            path = os.path.join(tmp, "plurals.sbl")
+            with open(path, "w", encoding="utf-8") as f:
+                f.write(source)
+            s = SnowballStemmer.from_snowball_source(path, cache=cache)
+            self.assertEqual(s.language, "plurals")
+            words = ["ponies", "cafés", "cats", "s", "grass"]
+            expected = ["pony", "café", "cat", "s", "gras"]
+            self.assertEqual([s.stem_word(w) for w in words], expected)
+            self.assertEqual(s.stem_words(words), expected)
+            self.assertEqual(s.stem_words_parallel(words * 200), expected * 200)
+            self.assertEqual(cache.stats().entries, len(words))
+
+            # The same source shares the cache with the first stemmer
+            before = cache.stats().hits
+            self.assertEqual(SnowballStemmer.from_snowball_source(path, cache=cache).stem_word("cats"), "cat")
+            self.assertEqual(cache.stats().hits, before + 1)
+
+            with open(path, "w") as f:
+                f.write("externals ( stem )\ndefine stem as (\n  [ 'x' ] <- y\n)\n")
+            with self.assertRaisesRegex(ValueError, "line 3"):
+                SnowballStemmer.from_snowball_source(path)
+            with self.assertRaises(OSError):
+                SnowballStemmer.from_snowball_source(os.path.join(tmp, "missing.sbl"))
+
+    def test_snowball_source_edge_cases(self):
+        """Test that bad programs leave words unstemmed or fail to load, and never crash"""
+        programs = {
+            # A failed delete shortens the word under a saved backward cursor
+            "shrink": """
+                routines ( r )
+                externals ( stem )
+                backwardmode ( define r as ( [ hop 2 ] tolimit try ( delete false ) 'a' ) )
+                define stem as backwards r
+            """,
+            "hop": """
+                externals ( stem )
+                define stem as ( try hop 9223372036854775807 [ hop 1 ] delete )
+            """,
+            # `=> s` copies the word, `$ s C` edits the copy without moving the cursor
+            "strings": """
+                strings ( t )
+                externals ( stem )
+                define stem as ( => t $ t ( [ 'h' ] <- 'J' ) not $ t 'x' tolimit <+ t )
+            """,
+        }
+        with tempfile.TemporaryDirectory() as tmp:
+            stemmers = {}
+            for name, source in programs.items():
+                path = os.path.join(tmp, name + ".sbl")
+                with open(path, "w") as f:
+                    f.write(source)
+                stemmers[name] = SnowballStemmer.from_snowball_source(path, cache=False)
+            self.assertEqual(stemmers["shrink"].stem_word("hello"), "hel")
+            self.assertEqual(stemmers["shrink"].stem_words(["a", "", "hello"]), ["a", "", "hel"])
+            self.assertEqual(stemmers["hop"].stem_word("hello"), "ello")
+            self.assertEqual(stemmers["strings"].stem_word("hello"), "helloJello")
+
+            path = os.path.join(tmp, "reverse.sbl")
+            with open(path, "w") as f:
+                f.write("externals ( stem )\ndefine stem as reverse true\n")
+            with self.assertRaisesRegex(ValueError, "'reverse' is not supported"):
+                SnowballStemmer.from_snowball_source(path)
+
+            # As with the Snowball compiler, a string can only appear once in an among
+            with open(path, "w") as f:
+                f.write("externals ( stem )\ndefine stem as backwards ( [substring] among ( 's' 'es' 's' (delete) ) )\n")
+            with self.assertRaisesRegex(ValueError, "'s' appears twice in among"):
+                SnowballStemmer.from_snowball_source(path)
+
+    def test_pickle_and_repr(self):
+        """Test that stemmers survive pickling and compare by configuration"""
+        s = SnowballStemmer("en-US", admission="tinylfu")
+        self.assertEqual(repr(s), 'SnowballStemmer("english", admission="tinylfu")')
+        self.assertEqual(repr(SnowballStemmer("de", cache="docs", admission=3)),
+                         'SnowballStemmer("german", cache=StemCache.named("docs"), admission=3)')
+        self.assertEqual(repr(SnowballStemmer("fr", cache=False)), 'SnowballStemmer("french", cache=False)')
+
+        restored = pickle.loads(pickle.dumps(s))
+        self.assertEqual(restored, s)
+        self.assertEqual(hash(restored), hash(s))
+        self.assertEqual(restored.stem_word("running"), "run")
+        self.assertNotEqual(s, SnowballStemmer("english"))
+        self.assertNotEqual(s, SnowballStemmer("spanish", admission="tinylfu"))
+        self.assertNotEqual(s, "english")
+
+        # Namespaces pickle by name; other caches come back empty with the
+        # same limits, still shared by the stemmers that shared them
+        named = SnowballStemmer("english", cache="docs")
+        self.assertEqual(pickle.loads(pickle.dumps(named)), named)
+        cache = StemCache(max_entries=100)
+        cache.set_l1_capacity(0)
+        en, es = SnowballStemmer("english", cache=cache), SnowballStemmer("spanish", cache=cache)
+        en.stem_word("running")
+        en2, es2 = pickle.loads(pickle.dumps([en, es]))
+        self.assertIs(en2.cache, es2.cache)
+        self.assertNotEqual(en2.cache, cache)
+        self.assertEqual(en2.cache.get_limits(), (100, None))
+        self.assertEqual(en2.cache.get_l1_capacity(), 0)
+        self.assertEqual(en2.cache.stats().entries, 0)
+
+        with tempfile.TemporaryDirectory() as tmp:
+            path = os.path.join(tmp, "plurals.sbl")
+            with open(path, "w") as f:
+                f.write("externals ( stem ) define stem as ( [ 's' ] delete )")
+            custom = SnowballStemmer.from_snowball_source(path, cache=False)
+        self.assertEqual(repr(custom), 'SnowballStemmer.from_snowball_source(<"plurals" program>, cache=False)')
+        restored = pickle.loads(pickle.dumps(custom))
+        self.assertEqual(restored, custom)
+        self.assertEqual(restored.stem_word("scat"), "cat")
+
+    def test_stem_text(self):
+        """Test that text is split on word boundaries, without punctuation, and stemmed"""
+        s = SnowballStemmer("english", cache=StemCache())
+        text = "The runners were RUNNING, quickly! In 2024 it cost $3.14 — can't stop."
+        self.assertEqual(s.stem_text(text),
+                         ["the", "runner", "were", "run", "quick", "in", "2024", "it", "cost", "3.14", "can't", "stop"])
+        self.assertEqual(s.stem_text(""), [])
+        self.assertEqual(SnowballStemmer("russian").stem_text("Новые смартфоны продаются!"), ["нов", "смартфон", "прода"])
+
+        texts = ["Cats and dogs.", "Hello, world!"] * 100
+        self.assertEqual(s.stem_texts(texts), [s.stem_text(t) for t in texts])
+        self.assertEqual(s.stem_texts([]), [])
+
+        t = Tokenizer(lowercase=False, min_length=3, max_length=7, numbers=False)
+        self.assertEqual(t.tokenize("The runners were running in 2024"), ["The", "runners", "were", "running"])
+        self.assertEqual(s.stem_texts(["Cats and dogs", "a b c"], tokenizer=t), [["Cat", "and", "dog"], []])
+        self.assertEqual(repr(t), "Tokenizer(lowercase=False, min_length=3, max_length=7, numbers=False)")
+        self.assertEqual(repr(pickle.loads(pickle.dumps(t))), repr(t))
+        with self.assertRaises(ValueError):
+            Tokenizer(min_length=5, max_length=2)
+
+    def test_stem_text_with_offsets(self):
+        """Test that stemmed tokens carry their surface form and char, byte and UTF-16 offsets"""
+        s = SnowballStemmer("english", cache=StemCache(), stopwords=True)
+        text = "Cafés 🎉 were RUNNING, the 𝒳 runners in 2024!"
+        tokens = s.stem_text_with_offsets(text)
+        self.assertEqual([t.stem for t in tokens], s.stem_text(text))
+        self.assertEqual([t.surface for t in tokens], ["Cafés", "RUNNING", "𝒳", "runners", "2024"])
+        encoded = text.encode("utf-8")
+        utf16 = text.encode("utf-16-le")
+        for t in tokens:
+            self.assertEqual(text[t.start:t.end], t.surface)
+            self.assertEqual(encoded[t.byte_start:t.byte_end].decode("utf-8"), t.surface)
+            self.assertEqual(utf16[2 * t.utf16_start:2 * t.utf16_end].decode("utf-16-le"), t.surface)
+        running = tokens[1]
+        self.assertEqual((running.stem, running.start, running.end), ("run", 13, 20))
+        self.assertEqual((running.byte_start, running.byte_end), (17, 24))
+        self.assertEqual((running.utf16_start, running.utf16_end), (14, 21))
+        self.assertEqual(repr(running), 'StemmedToken(stem="run", surface="RUNNING", start=13, end=20)')
+
+        texts = [text, "", "Hello, world!"] * 100
+        self.assertEqual(
+            [[(t.stem, t.start, t.end) for t in tokens] for tokens in s.stem_texts_with_offsets(texts)],
+            [[(t.stem, t.start, t.end) for t in s.stem_text_with_offsets(text)] for text in texts],
+        )
+        t = Tokenizer(min_length=3)
+        self.assertEqual([x.surface for x in s.stem_text_with_offsets(text, tokenizer=t)],
+                         ["Cafés", "RUNNING", "runners", "2024"])
+
+    def test_case_and_unicode_normalization(self):
+        """Test that equivalent spellings are normalized into one cache entry"""
+        cache = StemCache()
+        cache.set_l1_capacity(0)
+        s = SnowballStemmer("french", cache=cache, case="fold", normalize="NFC")
+        words = ["Cafés", "cafés", "CAFÉS", "cafés"]
+        self.assertEqual(s.stem_words(words), ["caf"] * 4)
+        self.assertEqual(s.stem_words_parallel(words * 200), ["caf"] * 800)
+        self.assertEqual([s.stem_word(w) for w in words], ["caf"] * 4)
+        self.assertEqual(cache.stats().entries, 1)
+
+        self.assertEqual(SnowballStemmer("german", case="fold").stem_word("STRASSE"),
+                         SnowballStemmer("german", case="fold").stem_word("Straße"))
+        self.assertEqual(SnowballStemmer("english", case="lower", cache=False).stem_word("RUNNING"), "run")
+        self.assertEqual(SnowballStemmer("english", normalize="NFKC").stem_word("ﬁshing"), "fish")
+        self.assertEqual(SnowballStemmer("english").stem_word("RUNNING"), "RUNNING")
+
+        warm_cache = StemCache()
+        SnowballStemmer("english", cache=warm_cache, case="lower").warm_cache(["Running", "running"])
+        self.assertEqual(warm_cache.stats().entries, 1)
+
+        restored = pickle.loads(pickle.dumps(SnowballStemmer("fr", case="lower", normalize="nfkc")))
+        self.assertEqual(restored, SnowballStemmer("french", case="lower", normalize="NFKC"))
+        self.assertNotEqual(restored, SnowballStemmer("french", case="lower"))
+        self.assertEqual(repr(restored), 'SnowballStemmer("french", case="lower", normalize="NFKC")')
+        with self.assertRaises(ValueError):
+            SnowballStemmer("english", case="upper")
+        with self.assertRaises(ValueError):
+            SnowballStemmer("english", normalize="NFD")
+
+    def test_orthography(self):
+        """Test the language-specific spelling rules applied with orthography=True"""
+        turkish = SnowballStemmer("turkish", case="lower", orthography=True)
+        self.assertEqual(turkish.stem_words(["KITAPLARI", "İzmir", "I\u0307zmir"]), ["kıtap", "izmir", "izmir"])
+        self.assertEqual(SnowballStemmer("turkish", case="fold", orthography=True).stem_word("İSTANBUL"), "istanbul")
+
+        greek = SnowballStemmer("greek", case="lower", orthography=True, cache=StemCache())
+        self.assertEqual(greek.stem_words(["ΜΠΑΤΑΡΊΕΣ", "μπαταρίες", "μπαταριες"]), ["μπαταρι"] * 3)
+        self.assertEqual(greek.cache.stats().entries, 1)
+
+        german = SnowballStemmer("german", orthography=True)
+        self.assertEqual(german.stem_words(["mueller", "müller", "strasse", "straße", "quelle", "abenteuer"]),
+                         ["mull", "mull", "strass", "strass", "quell", "abenteu"])
+
+        arabic = SnowballStemmer("arabic", orthography=True)
+        self.assertEqual(arabic.stem_word("كِتَابٌ"), arabic.stem_word("كتاب"))
+        self.assertEqual(arabic.stem_word("كتـــاب"), arabic.stem_word("كتاب"))
+        # Superscript alef, and maddah and hamza written as combining marks
+        self.assertEqual(arabic.stem_word("الرَّحْمٰنِ"), arabic.stem_word("الرحمن"))
+        self.assertEqual(arabic.stem_word("هٰذا"), arabic.stem_word("هذا"))
+        self.assertEqual(arabic.stem_word("سما\u0653ء"), arabic.stem_word("سماء"))
+
+        dutch = SnowballStemmer("dutch", case="lower", orthography=True)
+        self.assertEqual(dutch.stem_words(["ĳzer", "Ĳzer"]), [dutch.stem_word("ijzer")] * 2)
+
+        # Languages without rules of their own are unaffected
+        self.assertEqual(SnowballStemmer("english", orthography=True).stem_word("Running"), "Run")
+        self.assertNotEqual(german, SnowballStemmer("german"))
+        self.assertEqual(repr(german), 'SnowballStemmer("german", orthography=True)')
+        self.assertEqual(pickle.loads(pickle.dumps(german)), german)
+
+    def test_stopwords(self):
+        """Test that stopwords are dropped in Rust, from built-in or user lists"""
+        words = ["The", "runners", "were", "running", "and", "jumping"]
+        s = SnowballStemmer("english", stopwords=True)
+        self.assertEqual(s.stem_words(words), ["runner", "run", "jump"])
+        self.assertEqual(s.stem_words_parallel(words * 200), ["runner", "run", "jump"] * 200)
+        self.assertEqual(s.stem_text("The runners were running, and jumping!"), ["runner", "run", "jump"])
+        self.assertEqual(s.stem_texts(["the cats", "a dog"]), [["cat"], ["dog"]])
+        self.assertTrue(s.is_stopword("the"))
+        self.assertIn("because", s.stopwords)
+
+        # filter_stopwords works without stopwords=True and does not stem
+        plain = SnowballStemmer("english")
+        self.assertEqual(plain.filter_stopwords(words), ["runners", "running", "jumping"])
+        self.assertEqual(plain.stem_words(words)[1:], ["runner", "were", "run", "and", "jump"])
+
+        extra = SnowballStemmer("english", stopwords=True, extra_stopwords={"jumping"})
+        self.assertEqual(extra.stem_words(words), ["runner", "run"])
+        replaced = SnowballStemmer("english", stopwords=("running", "jumping"))
+        self.assertEqual(replaced.stem_words(words), ["The", "runner", "were", "and"])
+        self.assertEqual(replaced.stopwords, {"running", "jumping"})
+
+        for info in supported_languages():
+            self.assertTrue(info.has_stopwords, info.name)
+            self.assertTrue(SnowballStemmer(info.name, stopwords=True).stopwords, info.name)
+        self.assertEqual(SnowballStemmer("german", stopwords=True).stem_words(["Die", "kinder", "und", "häuser"]),
+                         ["kind", "haus"])
+
+        self.assertEqual(repr(extra), 'SnowballStemmer("english", stopwords=True, extra_stopwords=<1 word>)')
+        for stemmer in (s, extra, replaced):
+            self.assertEqual(pickle.loads(pickle.dumps(stemmer)), stemmer)
+        self.assertNotEqual(s, plain)
+        self.assertNotEqual(replaced, SnowballStemmer("english", stopwords=["running"]))
+        with self.assertRaises(TypeError):
+            SnowballStemmer("english", stopwords="the")
+
+    def test_protected_words_and_overrides(self):
+        """Test that protected words and overrides bypass the algorithm and the shared cache"""
+        cache = StemCache()
+        cache.set_l1_capacity(0)
+        s = SnowballStemmer("english", cache=cache, case="lower", protected=["news", "iOS"],
+                            overrides={"university": "university", "universities": "university"})
+        words = ["News", "news", "ios", "universities", "universe", "running"]
+        expected = ["news", "news", "ios", "university", "univers", "run"]
+        self.assertEqual([s.stem_word(w) for w in words], expected)
+        self.assertEqual(s.stem_words(words), expected)
+        self.assertEqual(s.stem_words_parallel(words * 200), expected * 200)
+        self.assertEqual(s.stem_text("News about universities"), ["news", "about", "university"])
+
+        # Only the algorithm's stems (universe, running, about) were cached,
+        # so other stemmers are unaffected
+        self.assertEqual(cache.stats().entries, 3)
+        other = SnowballStemmer("english", cache=cache)
+        self.assertEqual(other.stem_words(["ios", "university"]), ["io", "univers"])
+
+        with tempfile.TemporaryDirectory() as tmp:
+            tsv, json_path, words_path = (os.path.join(tmp, name) for name in ("o.tsv", "o.json", "p.txt"))
+            with open(tsv, "w", encoding="utf-8") as f:
+                f.write("# word\tstem\nuniversity\tuniversity\nnews\tnews\n")
+            with open(json_path, "w", encoding="utf-8") as f:
+                f.write('{"university": "university", "news": "news"}')
+            with open(words_path, "w", encoding="utf-8") as f:
+                f.write("news\n\nuniversity\n")
+            for kwargs in ({"overrides": tsv}, {"overrides": json_path}, {"protected": words_path}):
+                stemmer = SnowballStemmer("english", **kwargs)
+                self.assertEqual(stemmer.stem_words(["news", "university", "cats"]), ["news", "university", "cat"])
+
+            with open(tsv, "w", encoding="utf-8") as f:
+                f.write("university\tuniversity\nnews\n")
+            with self.assertRaisesRegex(ValueError, "line 2"):
+                SnowballStemmer("english", overrides=tsv)
+            with self.assertRaises(OSError):
+                SnowballStemmer("english", protected=os.path.join(tmp, "missing.txt"))
+
+        small = SnowballStemmer("english", protected=["news"], overrides={"a": "b", "c": "d"})
+        self.assertEqual(repr(small), 'SnowballStemmer("english", protected=<1 word>, overrides=<2 words>)')
+        self.assertEqual(pickle.loads(pickle.dumps(small)), small)
+        self.assertEqual(pickle.loads(pickle.dumps(s)).stem_words(words), expected)
+        self.assertNotEqual(s, SnowballStemmer("english", cache=cache, case="lower"))
+
+    def test_stem_recorder(self):
+        """Test that the surface forms behind each stem are counted by every method"""
+        r = StemRecorder()
+        s = SnowballStemmer("english", cache=StemCache(), stopwords=True, recorder=r)
+        self.assertEqual(s.stem_words(["computer", "computing", "the", "computing"]), ["comput"] * 3)
+        s.stem_words_parallel(["computation"] * 1000)
+        s.stem_word("computers")
+        s.stem_text("The Computer runs")
+        s.stem_texts(["running"] * 10)
+        # Text methods record words as they appear in the text, not lowercased
+        self.assertEqual(r.forms("comput"),
+                         [("computation", 1000), ("computing", 2), ("Computer", 1), ("computer", 1), ("computers", 1)])
+        self.assertEqual(r.display_form("comput"), "computation")
+        self.assertEqual(r.display_form("the"), None)
+        self.assertEqual(r.forms("the"), [])
+        self.assertEqual(r.display_forms(), {"comput": "computation", "run": "running"})
+        self.assertEqual(r.export()["run"], {"runs": 1, "running": 10})
+        self.assertEqual(len(r), 2)
+
+        self.assertIs(s.recorder, r)
+        self.assertIsNone(SnowballStemmer("english").recorder)
+        self.assertNotEqual(s, SnowballStemmer("english", cache=s.cache, stopwords=True))
+        restored = pickle.loads(pickle.dumps(s))
+        self.assertEqual(restored.recorder.export(), r.export())
+        self.assertEqual(StemRecorder(r.export()).export(), r.export())
+
+        # Ties go to the alphabetically first form
+        r.clear()
+        self.assertEqual(len(r), 0)
+        s.stem_words(["runs", "running"])
+        self.assertEqual(r.display_form("run"), "running")
+
+        # The same form is counted once whichever method saw it
+        r.clear()
+        s = SnowballStemmer("english", case="lower", recorder=r)
+        s.stem_words(["Computer"])
+        s.stem_text("Computer")
+        s.stem_texts(["Computer"])
+        self.assertEqual(r.export(), {"comput": {"Computer": 3}})
+
+    def test_token_policy(self):
+        """Test that numbers, URLs, emails, mentions, hashtags and emoji are kept, dropped or normalized"""
+        words = ["3.5GHz", "1,000,000", "https://Example.COM/Path/", "Bob@Example.com",
+                 "@JohnDoe", "#running", "👍🏽", "runners"]
+        cache = StemCache()
+        normalize = TokenPolicy(numbers="normalize", urls="normalize", emails="normalize",
+                                mentions="normalize", hashtags="normalize", emoji="normalize")
+        s = SnowballStemmer("english", cache=cache, token_policy=normalize)
+        expected = ["3.5ghz", "1000000", "https://example.com/Path", "bob@example.com",
+                    "@johndoe", "run", "👍", "runner"]
+        self.assertEqual(s.stem_words(words), expected)
+        self.assertEqual(s.stem_words_parallel(words * 100), expected * 100)
+        self.assertEqual([s.stem_word(w) for w in words], expected)
+        # Only the words reach the cache
+        self.assertEqual(cache.stats().entries, 2)
+        # Hashtags differing only in case are the same tag
+        self.assertEqual(s.stem_word("#Running"), "run")
+        self.assertEqual(s.stem_words(["#RUNNING", "#Runners"]), ["run", "runner"])
+        self.assertEqual(cache.stats().entries, 2)
+
+        keep = SnowballStemmer("english", token_policy=TokenPolicy(
+            numbers="keep", urls="keep", emails="keep", mentions="keep", hashtags="keep", emoji="keep"))
+        self.assertEqual(keep.stem_words(words), words[:-1] + ["runner"])
+        drop = SnowballStemmer("english", token_policy=TokenPolicy(
+            numbers="drop", urls="drop", emails="drop", mentions="drop", hashtags="drop", emoji="drop"))
+        self.assertEqual(drop.stem_words(words), ["runner"])
+        self.assertEqual(drop.stem_words_parallel(words * 100), ["runner"] * 100)
+        self.assertEqual(drop.stem_word("@JohnDoe"), "@JohnDoe")
+        self.assertEqual(SnowballStemmer("english").stem_words(["@JohnDoe", "#running"]), ["@JohnDo", "#run"])
+
+        text = "See https://Example.com/Path, mail Bob@Example.COM (or @JohnDoe) #Running 🎉 at 3.5GHz!"
+        self.assertEqual(s.stem_text(text), ["see", "https://example.com/Path", "mail", "bob@example.com",
+                                             "or", "@johndoe", "run", "🎉", "at", "3.5ghz"])
+        self.assertEqual(drop.stem_text(text), ["see", "mail", "or", "at"])
+        self.assertEqual(SnowballStemmer("english").stem_text("mail bob@example.com 🎉"),
+                         ["mail", "bob", "example.com"])
+        for t in s.stem_text_with_offsets(text):
+            self.assertEqual(text[t.start:t.end], t.surface)
+
+        p = TokenPolicy(urls="drop", emoji="keep")
+        self.assertEqual((p.urls, p.emoji, p.numbers), ("drop", "keep", None))
+        self.assertEqual(repr(p), 'TokenPolicy(urls="drop", emoji="keep")')
+        self.assertEqual(pickle.loads(pickle.dumps(p)), p)
+        self.assertEqual(pickle.loads(pickle.dumps(keep)), keep)
+        self.assertNotEqual(s, SnowballStemmer("english", cache=cache))
+        with self.assertRaises(ValueError):
+            TokenPolicy(urls="remove")
+
     def test_cache_with_repeated_words(self):
         """Test that repeated words benefit from caching"""
         s = SnowballStemmer('english')
@@ -75,5 +642,385 @@ class TestRustStemmer(unittest.TestCase):
         self.assertIsNotNone(result_en)
         self.assertIsNotNone(result_es)
 
+    def test_cache_limits(self):
+        """Test that cache limits can be changed and read back"""
+        try:
+            set_cache_limits(max_entries=500, max_bytes=1 << 20)
+            self.assertEqual(get_cache_limits(), (500, 1 << 20))
+
+            set_cache_limits(max_entries=None)
+            self.assertEqual(get_cache_limits(), (None, None))
+        finally:
+            set_cache_limits()
+        self.assertEqual(get_cache_limits(), (100_000, None))
+
+    def test_cache_eviction_keeps_results_correct(self):
+        """Test that stemming stays correct while a tiny cache evicts entries"""
+        s = SnowballStemmer('english')
+        words = [f"running{i}" for i in range(2000)] + ["running", "jumps"]
+        expected = s.stem_words(words)
+        try:
+            set_cache_limits(max_entries=64)
+            self.assertEqual(s.stem_words(words), expected)
+            self.assertEqual(s.stem_words_parallel(words), expected)
+            self.assertEqual(s.stem_word("running"), "run")
+        finally:
+            set_cache_limits()
+
+    def test_cache_stats(self):
+        """Test that lookups, inserts and entries are counted"""
+        s = SnowballStemmer('english')
+        before = cache_stats()
+
+        s.stem_word("statisticsword")
+        s.stem_word("statisticsword")
+        s.stem_words_parallel(["statisticsparallel"] * 10)
+
+        after = s.cache_stats()
+        self.assertGreaterEqual(after.misses - before.misses, 2)
+        self.assertGreaterEqual(after.hits - before.hits, 10)
+        self.assertGreaterEqual(after.inserts - before.inserts, 2)
+        self.assertGreater(after.entries_per_language[3], 0)  # english
+        self.assertEqual(after.entries, sum(after.entries_per_language.values()))
+        self.assertGreater(after.memory_bytes, 0)
+        self.assertTrue(0.0 <= after.hit_rate <= 1.0)
+
+    def test_cache_stats_evictions(self):
+        """Test that evictions are counted and entries stay within the limit"""
+        s = SnowballStemmer('english')
+        try:
+            set_cache_limits(max_entries=320)
+            before = cache_stats()
+            s.stem_words([f"evicted{i}" for i in range(1000)])
+            after = cache_stats()
+            self.assertLessEqual(after.entries, 320)
+            self.assertGreater(after.evictions, before.evictions)
+        finally:
+            set_cache_limits()
+
+    def test_cache_limits_below_shard_count(self):
+        """Test that limits smaller than the number of shards still hold"""
+        cache = StemCache(max_entries=10, shards=64, l1_capacity=0)
+        s = SnowballStemmer('english', cache=cache)
+        words = [f"limited{i}" for i in range(1000)]
+        s.stem_words(words)
+        s.stem_words_parallel(words)
+        self.assertGreater(cache.stats().entries, 0)
+        self.assertLessEqual(cache.stats().entries, 10)
+
+        # Entries move with the shard count, and stay reachable
+        cache.set_limits(max_entries=None)
+        before = cache.stats()
+        s.stem_words(words)
+        self.assertEqual(cache.stats().hits - before.hits, before.entries)
+        self.assertEqual(cache.stats().entries, 1000)
+
+        cache.set_limits(max_entries=0)
+        self.assertEqual(cache.stats().entries, 0)
+        s.stem_words(words)
+        self.assertEqual(cache.stats().entries, 0)
+
+        # A byte budget too small to split 64 ways still caches something
+        cache.set_limits(max_entries=None, max_bytes=2000)
+        s.stem_words(words)
+        self.assertGreater(cache.stats().entries, 0)
+        self.assertLessEqual(cache.stats().memory_bytes, 2000)
+        self.assertEqual(s.stem_words(words), [f"limited{i}" for i in range(1000)])
+
+    def test_clear_cache_per_language(self):
+        """Test that clearing one language leaves the others cached"""
+        s_en = SnowballStemmer('english')
+        s_es = SnowballStemmer('spanish')
+        s_en.stem_words(["running", "jumping"])
+        s_es.stem_words(["corriendo", "saltando"])
+
+        clear_cache('english')
+        stats = cache_stats()
+        self.assertNotIn(3, stats.entries_per_language)  # english
+        self.assertGreater(stats.entries_per_language[14], 0)  # spanish
+        self.assertEqual(s_en.stem_word("running"), "run")
+
+        with self.assertRaises(ValueError):
+            clear_cache('invalid_lang')
+
+    def test_clear_and_shrink_cache(self):
+        """Test that clear_cache empties the cache and shrink_cache keeps entries"""
+        s = SnowballStemmer('english')
+        s.stem_words(["running", "jumping", "swimming"])
+        before = cache_stats()
+
+        shrink_cache()
+        shrunk = cache_stats()
+        self.assertEqual(shrunk.entries, before.entries)
+        self.assertEqual(shrunk.memory_bytes, before.memory_bytes)
+
+        clear_cache()
+        cleared = cache_stats()
+        self.assertEqual(cleared.entries, 0)
+        self.assertEqual(cleared.memory_bytes, 0)
+        self.assertEqual(cleared.entries_per_language, {})
+        self.assertEqual(s.stem_words(["running", "jumping"]), ["run", "jump"])
+
+    def test_save_and_load_cache(self):
+        """Test that a saved cache can be reloaded after clearing it"""
+        s_en = SnowballStemmer('english')
+        s_es = SnowballStemmer('spanish')
+        clear_cache()
+        s_en.stem_words(["running", "jumping", "swimming"])
+        s_es.stem_words(["corriendo", "saltando"])
+
+        with tempfile.TemporaryDirectory() as tmp:
+            path = os.path.join(tmp, "stems.bin")
+            self.assertEqual(save_cache(path), 5)
+
+            clear_cache()
+            self.assertEqual(load_cache(path), 5)
+
+        stats = cache_stats()
+        self.assertEqual(stats.entries, 5)
+        self.assertEqual(stats.entries_per_language, {3: 3, 14: 2})
+
+        before = cache_stats()
+        self.assertEqual(s_en.stem_words(["running", "jumping"]), ["run", "jump"])
+        self.assertEqual(cache_stats().hits - before.hits, 2)
+
+    def test_load_cache_rejects_invalid_files(self):
+        """Test that foreign or corrupted files are rejected"""
+        with tempfile.TemporaryDirectory() as tmp:
+            path = os.path.join(tmp, "stems.bin")
+            with open(path, "wb") as f:
+                f.write(b"not a cache file")
+            with self.assertRaises(ValueError):
+                load_cache(path)
+
+            SnowballStemmer('english').stem_word("running")
+            save_cache(path)
+            with open(path, "rb") as f:
+                data = f.read()
+            with open(path, "wb") as f:
+                f.write(data[:-1])
+            with self.assertRaises(ValueError):
+                load_cache(path)
+
+            with self.assertRaises(OSError):
+                load_cache(os.path.join(tmp, "missing.bin"))
+
+    def test_load_cache_checks_algorithm_versions(self):
+        """Test that stems of a changed in-crate algorithm are not loaded"""
+        cache = StemCache()
+        SnowballStemmer('hindi', cache=cache).stem_word("किताबें")
+        with tempfile.TemporaryDirectory() as tmp:
+            path = os.path.join(tmp, "stems.bin")
+            self.assertEqual(cache.save(path), 1)
+            with open(path, "rb") as f:
+                data = bytearray(f.read())
+            self.assertEqual(data[:6], b"PRSC\x02\x00")
+            version = data[7:7 + data[6]].decode()
+            self.assertRegex(version, r"^\d+\.\d+\.\d+")
+
+            # One language: its discriminant, then the fingerprint of its source
+            languages = 7 + data[6]
+            self.assertEqual(data[languages:languages + 2], b"\x01\x12")
+            self.assertNotEqual(data[languages + 2:languages + 10], bytes(8))
+            self.assertEqual(StemCache().load(path), 1)
+
+            data[languages + 2] ^= 0xff
+            with open(path, "wb") as f:
+                f.write(data)
+            with self.assertRaisesRegex(ValueError, "hindi"):
+                StemCache().load(path)
+
+    def test_cache_objects_are_isolated(self):
+        """Test that stemmers given different cache objects don't share entries"""
+        tenant_a = StemCache(max_entries=1000)
+        tenant_b = StemCache()
+        s_a1 = SnowballStemmer('english', cache=tenant_a)
+        s_a2 = SnowballStemmer('english', cache=tenant_a)
+        s_b = SnowballStemmer('english', cache=tenant_b)
+
+        s_a1.stem_words(["running", "jumping"])
+        self.assertEqual(tenant_a.stats().entries, 2)
+        self.assertEqual(tenant_b.stats().entries, 0)
+
+        s_a2.stem_word("running")
+        self.assertEqual(tenant_a.stats().hits, 1)
+        s_b.stem_word("running")
+        self.assertEqual(tenant_b.stats().misses, 1)
+
+        self.assertEqual(tenant_a.get_limits(), (1000, None))
+        self.assertEqual(s_a1.cache, s_a2.cache)
+        self.assertNotEqual(s_a1.cache, s_b.cache)
+
+        tenant_a.clear()
+        self.assertEqual(tenant_a.stats().entries, 0)
+
+    def test_cache_namespaces(self):
+        """Test that stemmers using the same namespace name share one cache"""
+        s1 = SnowballStemmer('english', cache="test-namespace")
+        s2 = SnowballStemmer('english', cache="test-namespace")
+        self.assertEqual(s1.cache, s2.cache)
+        self.assertEqual(s1.cache, StemCache.named("test-namespace"))
+        self.assertEqual(s1.cache.name, "test-namespace")
+        self.assertNotEqual(s1.cache, StemCache.default())
+
+        self.assertEqual(SnowballStemmer('english').cache, StemCache.default())
+        self.assertEqual(SnowballStemmer('english', cache="default").cache, StemCache.default())
+        self.assertIsNone(SnowballStemmer('english', cache=False).cache)
+
+    def test_warm_cache(self):
+        """Test that warm-up fills the cache so later lookups are hits"""
+        cache = StemCache()
+        s = SnowballStemmer('english', cache=cache)
+        self.assertEqual(s.warm_cache(["running", "jumping", "swimming"]), 3)
+        self.assertEqual(cache.stats().entries, 3)
+
+        self.assertEqual(s.stem_words(["running", "jumping"]), ["run", "jump"])
+        self.assertEqual(cache.stats().hits, 2)
+        self.assertEqual(cache.stats().misses, 0)
+
+        with self.assertRaises(ValueError):
+            SnowballStemmer('english', cache=False).warm_cache(["running"])
+
+    def test_warm_cache_follows_token_policy_and_stopwords(self):
+        """Test that warm-up skips the words stemming would drop or keep verbatim"""
+        cache = StemCache()
+        s = SnowballStemmer('english', cache=cache, stopwords=True, token_policy=TokenPolicy(
+            urls="keep", numbers="keep", mentions="drop", hashtags="normalize"))
+        words = ["running", "the", "https://example.com/", "3.5GHz", "@JohnDoe", "#Jumping"]
+        self.assertEqual(s.warm_cache(words), 2)
+        self.assertEqual(cache.stats().entries, 2)
+
+        self.assertEqual(s.stem_words(words), ["run", "https://example.com/", "3.5GHz", "jump"])
+        self.assertEqual(cache.stats().hits, 2)
+        self.assertEqual(cache.stats().misses, 0)
+
+    def test_warm_cache_from_file_in_background(self):
+        """Test that background warm-up from a vocabulary file can be awaited"""
+        cache = StemCache()
+        s = SnowballStemmer('english', cache=cache)
+        with tempfile.TemporaryDirectory() as tmp:
+            path = os.path.join(tmp, "vocab.txt")
+            with open(path, "w") as f:
+                f.write("running 120\njumping\t45\n\nswimming\n")
+
+            warmup = s.warm_cache_from_file(path, background=True)
+            self.assertEqual(warmup.wait(), 3)
+            self.assertTrue(warmup.done)
+            self.assertEqual(cache.stats().entries, 3)
+            self.assertEqual(s.stem_word("swimming"), "swim")
+            self.assertEqual(cache.stats().hits, 1)
+
+            failed = s.warm_cache_from_file(os.path.join(tmp, "missing.txt"), background=True)
+            with self.assertRaises(OSError):
+                failed.wait()
+
+    def test_admission_after_sightings(self):
+        """Test that words are only cached once seen the required number of times"""
+        cache = StemCache()
+        s = SnowballStemmer('english', cache=cache, admission=2)
+
+        self.assertEqual(s.stem_word("hapaxes"), "hapax")
+        self.assertEqual(cache.stats().entries, 0)
+        self.assertEqual(cache.stats().rejections, 1)
+
+        self.assertEqual(s.stem_word("hapaxes"), "hapax")
+        self.assertEqual(cache.stats().entries, 1)
+        s.stem_word("hapaxes")
+        self.assertEqual(cache.stats().hits, 1)
+
+    def test_tinylfu_admission_keeps_frequent_words(self):
+        """Test that one-off tokens don't evict frequent words under TinyLFU"""
+        rare = [f"typo{i}x" for i in range(1000)]
+        for admission, expect_hit in (("always", False), ("tinylfu", True)):
+            cache = StemCache(max_entries=32, shards=32, l1_capacity=0)
+            s = SnowballStemmer('english', cache=cache, admission=admission)
+            s.stem_words(["running"] * 10)
+            s.stem_words_parallel(rare)
+
+            before = cache.stats().hits
+            self.assertEqual(s.stem_word("running"), "run")
+            self.assertEqual(cache.stats().hits - before, int(expect_hit))
+
+    def test_tinylfu_counts_l1_hits(self):
+        """Test that lookups answered by the L1 cache count as sightings"""
+        cache = StemCache(max_entries=2, shards=1, l1_capacity=256)
+        s = SnowballStemmer('english', cache=cache, admission="tinylfu")
+        s.stem_word("running")
+        s.stem_words(["running"] * 20)
+        self.assertEqual(cache.stats().l1_hits, 20)
+        s.stem_word("jumping")
+
+        # "running" is least recently used but looked up far more often
+        s.stem_words(["walking"] * 3)
+        self.assertEqual(cache.stats().rejections, 3)
+        cache.set_l1_capacity(0)
+        misses = cache.stats().misses
+        self.assertEqual(s.stem_word("running"), "run")
+        self.assertEqual(cache.stats().misses, misses)
+
+    def test_invalid_admission(self):
+        with self.assertRaises(ValueError):
+            SnowballStemmer('english', admission="bogus")
+        with self.assertRaises(ValueError):
+            SnowballStemmer('english', admission=0)
+
+    def test_l1_cache(self):
+        """Test that repeated lookups are served by the thread-local L1 cache"""
+        cache = StemCache(shards=64, l1_capacity=128)
+        self.assertEqual(cache.shards, 64)
+        self.assertEqual(cache.get_l1_capacity(), 128)
+        s = SnowballStemmer('english', cache=cache)
+
+        s.stem_words(["running"] * 5)
+        stats = cache.stats()
+        self.assertEqual(stats.misses, 1)
+        self.assertEqual(stats.l1_hits, 4)
+        self.assertEqual(stats.hits, 4)
+
+        self.assertEqual(s.stem_words_parallel(["running"] * 1000), ["run"] * 1000)
+        self.assertEqual(cache.stats().hits, 1004)
+
+        cache.set_l1_capacity(0)
+        s.stem_word("running")
+        self.assertEqual(cache.stats().l1_hits, 1003)
+
+    def test_l1_hits_refresh_shared_cache(self):
+        """Test that words answered by the L1 cache are not evicted as least recently used"""
+        cache = StemCache(max_entries=20, shards=1, l1_capacity=256)
+        s = SnowballStemmer('english', cache=cache)
+        s.stem_word("running")
+        for round in range(5):
+            s.stem_words(["running"] * 40)
+            s.stem_words([f"filler{round}x{i}" for i in range(10)])
+        self.assertGreater(cache.stats().l1_hits, 0)
+
+        cache.set_l1_capacity(0)
+        misses = cache.stats().misses
+        self.assertEqual(s.stem_word("running"), "run")
+        self.assertEqual(cache.stats().misses, misses)
+
+    def test_default_shard_count_from_environment(self):
+        """Test that PY_RUST_STEMMERS_SHARDS sets the shard count of new caches"""
+        os.environ["PY_RUST_STEMMERS_SHARDS"] = "8"
+        try:
+            self.assertEqual(StemCache().shards, 8)
+            self.assertEqual(StemCache(shards=4).shards, 4)
+        finally:
+            del os.environ["PY_RUST_STEMMERS_SHARDS"]
+        self.assertGreaterEqual(StemCache().shards, 32)
+
+    def test_clear_invalidates_l1_cache(self):
+        """Test that clearing the cache also drops thread-local L1 entries"""
+        cache = StemCache()
+        s = SnowballStemmer('english', cache=cache)
+        s.stem_words(["running", "running"])
+        self.assertEqual(cache.stats().l1_hits, 1)
+
+        cache.clear()
+        s.stem_word("running")
+        stats = cache.stats()
+        self.assertEqual(stats.l1_hits, 1)
+        self.assertEqual(stats.misses, 2)
+
 if __name__ == '__main__':
     unittest.main()